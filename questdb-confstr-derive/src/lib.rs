@@ -0,0 +1,252 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+#![doc = include_str!("../README.md")]
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::ext::IdentExt;
+use syn::meta::ParseNestedMeta;
+use syn::punctuated::Punctuated;
+use syn::{
+    parenthesized, parse_macro_input, Attribute, Data, DeriveInput, Fields, GenericArgument,
+    LitStr, Path, PathArguments, Token, Type,
+};
+
+/// Derive `TryFrom<&questdb_confstr::ConfStr>`.
+///
+/// See the crate documentation for the supported attributes.
+#[proc_macro_derive(FromConfStr, attributes(confstr))]
+pub fn derive_from_conf_str(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+#[derive(Default)]
+struct StructAttrs {
+    services: Option<Vec<LitStr>>,
+}
+
+enum FieldDefault {
+    None,
+    Trait,
+    Path(Path),
+}
+
+struct FieldAttrs {
+    key: Option<LitStr>,
+    default: FieldDefault,
+    required: bool,
+    secret: bool,
+    services: Option<Vec<LitStr>>,
+    parse_with: Option<Path>,
+}
+
+fn parse_services(meta: &ParseNestedMeta) -> syn::Result<Vec<LitStr>> {
+    let content;
+    parenthesized!(content in meta.input);
+    let services = Punctuated::<LitStr, Token![,]>::parse_terminated(&content)?;
+    Ok(services.into_iter().collect())
+}
+
+fn parse_struct_attrs(attrs: &[Attribute]) -> syn::Result<StructAttrs> {
+    let mut parsed = StructAttrs::default();
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("confstr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("services") {
+                parsed.services = Some(parse_services(&meta)?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported confstr attribute, expected `services(...)`"))
+            }
+        })?;
+    }
+    Ok(parsed)
+}
+
+fn parse_field_attrs(attrs: &[Attribute]) -> syn::Result<FieldAttrs> {
+    let mut parsed = FieldAttrs {
+        key: None,
+        default: FieldDefault::None,
+        required: false,
+        secret: false,
+        services: None,
+        parse_with: None,
+    };
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("confstr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("key") {
+                parsed.key = Some(meta.value()?.parse()?);
+            } else if meta.path.is_ident("default") {
+                parsed.default = match meta.input.peek(Token![=]) {
+                    true => FieldDefault::Path(meta.value()?.parse::<LitStr>()?.parse()?),
+                    false => FieldDefault::Trait,
+                };
+            } else if meta.path.is_ident("required") {
+                parsed.required = true;
+            } else if meta.path.is_ident("secret") {
+                parsed.secret = true;
+            } else if meta.path.is_ident("services") {
+                parsed.services = Some(parse_services(&meta)?);
+            } else if meta.path.is_ident("parse_with") {
+                parsed.parse_with = Some(meta.value()?.parse::<LitStr>()?.parse()?);
+            } else {
+                return Err(meta.error(
+                    "unsupported confstr attribute, expected one of \
+                     `key`, `default`, `required`, `secret`, `services` or `parse_with`",
+                ));
+            }
+            Ok(())
+        })?;
+    }
+    Ok(parsed)
+}
+
+/// The `T` in `Option<T>`, if the type is an `Option`.
+fn option_inner(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner),
+        _ => None,
+    }
+}
+
+/// True if the type's last path segment is `name`, e.g. `bool` or `std::time::Duration`.
+fn is_type(ty: &Type, name: &str) -> bool {
+    match ty {
+        Type::Path(type_path) => type_path
+            .path
+            .segments
+            .last()
+            .is_some_and(|segment| segment.ident == name && segment.arguments.is_empty()),
+        _ => false,
+    }
+}
+
+fn expand(input: DeriveInput) -> syn::Result<TokenStream2> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FromConfStr can only be derived for structs with named fields",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "FromConfStr can only be derived for structs with named fields",
+        ));
+    };
+
+    let struct_attrs = parse_struct_attrs(&input.attrs)?;
+    let check_service = struct_attrs.services.map(|services| {
+        quote! {
+            ::questdb_confstr::__private::check_service(&mut __errors, conf_str, &[#(#services),*]);
+        }
+    });
+
+    let mut extract = Vec::new();
+    let mut construct = Vec::new();
+    for (index, field) in fields.named.iter().enumerate() {
+        let ident = field.ident.as_ref().expect("named field");
+        let attrs = parse_field_attrs(&field.attrs)?;
+        let key = attrs
+            .key
+            .unwrap_or_else(|| LitStr::new(&ident.unraw().to_string(), ident.span()));
+        let var = format_ident!("__field{}", index);
+        let optional = option_inner(&field.ty);
+        let value_ty = optional.unwrap_or(&field.ty);
+        let has_default = !matches!(attrs.default, FieldDefault::None);
+        if optional.is_some() && has_default {
+            return Err(syn::Error::new_spanned(
+                &field.ty,
+                "`default` is not supported on `Option` fields",
+            ));
+        }
+        let required = attrs.required || (optional.is_none() && !has_default);
+        let secret = attrs.secret;
+
+        let get = match &attrs.parse_with {
+            Some(parse_with) => quote! {
+                ::questdb_confstr::__private::parse_with(conf_str, #key, #secret, #parse_with)
+            },
+            None if is_type(value_ty, "bool") => quote! { conf_str.get_bool(#key) },
+            None if is_type(value_ty, "Duration") => quote! { conf_str.get_duration_ms(#key) },
+            None => quote! { conf_str.get_parsed::<#value_ty>(#key) },
+        };
+        if let Some(services) = &attrs.services {
+            extract.push(quote! {
+                ::questdb_confstr::__private::check_key_service(
+                    &mut __errors, conf_str, #key, &[#(#services),*]);
+            });
+        }
+        extract.push(quote! {
+            let #var: ::core::option::Option<#value_ty> =
+                ::questdb_confstr::__private::field(&mut __errors, #key, #required, #get);
+        });
+
+        let value = match (&attrs.default, optional) {
+            (FieldDefault::Path(path), _) => quote! { #var.unwrap_or_else(#path) },
+            (FieldDefault::Trait, _) => quote! { #var.unwrap_or_default() },
+            (FieldDefault::None, Some(_)) => quote! { #var },
+            (FieldDefault::None, None) => quote! {
+                #var.expect("missing required keys are reported above")
+            },
+        };
+        construct.push(quote! { #ident: #value });
+    }
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::core::convert::TryFrom<&::questdb_confstr::ConfStr>
+            for #name #ty_generics #where_clause
+        {
+            type Error = ::questdb_confstr::FromConfStrError;
+
+            fn try_from(
+                conf_str: &::questdb_confstr::ConfStr,
+            ) -> ::core::result::Result<Self, Self::Error> {
+                let mut __errors = ::questdb_confstr::__private::Vec::new();
+                #check_service
+                #(#extract)*
+                ::questdb_confstr::__private::finish(__errors)?;
+                ::core::result::Result::Ok(Self {
+                    #(#construct,)*
+                })
+            }
+        }
+    })
+}
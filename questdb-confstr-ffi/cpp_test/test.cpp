@@ -24,6 +24,8 @@
 
 #include <iostream>
 #include <unordered_map>
+#include <utility>
+#include <vector>
 
 #include <questdb/conf_str.hpp>
 
@@ -70,3 +72,54 @@ TEST_CASE("iter params") {
     CHECK(params["port"] == "9000");
 }
 
+
+TEST_CASE("iter params in order") {
+    const auto c1 = conf_str::parse("http::zeta=1;alpha=2;mid=3;");
+    std::vector<std::pair<std::string, std::string>> params;
+    for (auto it = c1.begin(); it != c1.end(); ++it) {
+        params.emplace_back(it.key(), it.value());
+    }
+    REQUIRE(params.size() == 3);
+    CHECK(params[0] == std::make_pair(std::string{"zeta"}, std::string{"1"}));
+    CHECK(params[1] == std::make_pair(std::string{"alpha"}, std::string{"2"}));
+    CHECK(params[2] == std::make_pair(std::string{"mid"}, std::string{"3"}));
+}
+
+TEST_CASE("duplicate keys") {
+    const auto str = "http::addr=a:9000;addr=b:9000;";
+    REQUIRE_THROWS_AS(conf_str::parse(str), parse_err);
+
+    const auto last = conf_str::parse(
+        str, questdb_conf_str_duplicate_keys_last_wins);
+    CHECK(last.get("addr") == "b:9000");
+    CHECK(last.get_all("addr") == std::vector<std::string_view>{"b:9000"});
+
+    const auto all = conf_str::parse(
+        str, questdb_conf_str_duplicate_keys_collect);
+    CHECK(all.get("addr") == "a:9000");
+    CHECK(all.get_all("addr") ==
+        std::vector<std::string_view>{"a:9000", "b:9000"});
+    CHECK(all.get_all("host").empty());
+}
+
+TEST_CASE("invalid duplicate keys policy") {
+    questdb_conf_str_parse_err* err = nullptr;
+    const auto str = "http::addr=a:9000;";
+    const auto c1 = ::questdb_conf_str_parse_with(str, 18, 7, &err);
+    CHECK(c1 == nullptr);
+    REQUIRE(err != nullptr);
+    CHECK(std::string_view{err->msg, err->msg_len} ==
+        "invalid duplicate keys policy 7");
+    ::questdb_conf_str_parse_err_free(err);
+}
+
+TEST_CASE("invalid utf-8") {
+    const auto str = "http::user=a\xff" "b;";
+    REQUIRE_THROWS_AS(conf_str::parse(str), parse_err);
+    try {
+        conf_str::parse(str);
+    } catch (const parse_err& e) {
+        CHECK(e.msg() == "invalid UTF-8 sequence at position 12");
+        CHECK(e.pos() == 12);
+    }
+}
@@ -21,11 +21,23 @@ void questdb_conf_str_parse_err_free(questdb_conf_str_parse_err* err);
 
 typedef struct questdb_conf_str_iter questdb_conf_str_iter;
 
+typedef enum questdb_conf_str_duplicate_keys {
+    questdb_conf_str_duplicate_keys_error = 0,
+    questdb_conf_str_duplicate_keys_last_wins = 1,
+    questdb_conf_str_duplicate_keys_collect = 2,
+} questdb_conf_str_duplicate_keys;
+
 questdb_conf_str* questdb_conf_str_parse(
     const char* str,
     size_t len,
     questdb_conf_str_parse_err** err_out);
 
+questdb_conf_str* questdb_conf_str_parse_with(
+    const char* str,
+    size_t len,
+    int duplicate_keys, /* A `questdb_conf_str_duplicate_keys` value. */
+    questdb_conf_str_parse_err** err_out);
+
 const char* questdb_conf_str_service(
     const questdb_conf_str* conf_str,
     size_t* len_out);
@@ -39,6 +51,12 @@ const char* questdb_conf_str_get(
 questdb_conf_str_iter* questdb_conf_str_iter_pairs(
     const questdb_conf_str* conf_str);
 
+/* Iterate over every value of `key`, as key-value pairs, in input order. */
+questdb_conf_str_iter* questdb_conf_str_get_all(
+    const questdb_conf_str* conf_str,
+    const char* key,
+    size_t key_len);
+
 bool questdb_conf_str_iter_next(
     questdb_conf_str_iter* iter,
     const char** key_out,
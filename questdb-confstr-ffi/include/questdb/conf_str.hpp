@@ -4,6 +4,7 @@
 
 #include <optional>
 #include <string_view>
+#include <vector>
 
 namespace questdb::conf_str
 {
@@ -107,10 +108,14 @@ private:
 class conf_str
 {
 public:
-    static conf_str parse(std::string_view str)
+    static conf_str parse(
+        std::string_view str,
+        ::questdb_conf_str_duplicate_keys duplicate_keys =
+            ::questdb_conf_str_duplicate_keys_error)
     {
         questdb_conf_str_parse_err* err = nullptr;
-        auto res = ::questdb_conf_str_parse(str.data(), str.size(), &err);
+        auto res = ::questdb_conf_str_parse_with(
+            str.data(), str.size(), duplicate_keys, &err);
         if (res != nullptr)
         {
             return conf_str{res};
@@ -139,6 +144,17 @@ public:
         return {};
     }
 
+    std::vector<std::string_view> get_all(std::string_view key) const
+    {
+        std::vector<std::string_view> values;
+        pair_iter it{ ::questdb_conf_str_get_all(_impl, key.data(), key.size()) };
+        for (; it != end(); ++it)
+        {
+            values.push_back(it.value());
+        }
+        return values;
+    }
+
     pair_iter begin() const noexcept
     {
         auto iter = ::questdb_conf_str_iter_pairs(_impl);
@@ -25,9 +25,8 @@
 #![doc = include_str!("../README.md")]
 #![allow(clippy::missing_safety_doc)]
 
-use questdb_confstr::{parse_conf_str, ConfStr};
-use std::collections::hash_map;
-use std::os::raw::c_char;
+use questdb_confstr::{parse_conf_str_bytes_with, ConfStr, DuplicateKeys, ParseOptions};
+use std::os::raw::{c_char, c_int};
 use std::ptr;
 use std::slice;
 
@@ -58,26 +57,45 @@ pub unsafe extern "C" fn questdb_conf_str_parse_err_free(err: *mut questdb_conf_
     }
 }
 
+/// Map a `questdb_conf_str_duplicate_keys` value from C.
+///
+/// Taken as an `int` rather than a `#[repr(C)]` enum, since C callers can
+/// pass any value and an out-of-range enum would be undefined behaviour.
+fn duplicate_keys_from_c(duplicate_keys: c_int) -> Option<DuplicateKeys> {
+    match duplicate_keys {
+        0 => Some(DuplicateKeys::Error),
+        1 => Some(DuplicateKeys::LastWins),
+        2 => Some(DuplicateKeys::Collect),
+        _ => None,
+    }
+}
+
 #[no_mangle]
 pub unsafe extern "C" fn questdb_conf_str_parse(
     str: *const c_char,
     len: usize,
     err_out: *mut *mut questdb_conf_str_parse_err,
 ) -> *mut questdb_conf_str {
-    let input = slice::from_raw_parts(str as *const u8, len);
-    let input_str = match std::str::from_utf8(input) {
-        Ok(s) => s,
-        Err(utf8err) => {
-            let first_bad_byte = utf8err.valid_up_to();
-            *err_out = new_err(
-                format!("invalid UTF-8 sequence at position {}", first_bad_byte),
-                first_bad_byte,
-            );
-            return ptr::null_mut();
-        }
-    };
+    questdb_conf_str_parse_with(str, len, 0, err_out)
+}
 
-    match parse_conf_str(input_str) {
+#[no_mangle]
+pub unsafe extern "C" fn questdb_conf_str_parse_with(
+    str: *const c_char,
+    len: usize,
+    duplicate_keys: c_int,
+    err_out: *mut *mut questdb_conf_str_parse_err,
+) -> *mut questdb_conf_str {
+    let Some(duplicate_keys) = duplicate_keys_from_c(duplicate_keys) else {
+        *err_out = new_err(
+            format!("invalid duplicate keys policy {}", duplicate_keys),
+            0,
+        );
+        return ptr::null_mut();
+    };
+    let input = slice::from_raw_parts(str as *const u8, len);
+    let options = ParseOptions::new().duplicate_keys(duplicate_keys);
+    match parse_conf_str_bytes_with(input, &options) {
         Ok(conf_str) => Box::into_raw(Box::new(questdb_conf_str { inner: conf_str })),
         Err(err) => {
             *err_out = new_err(err.to_string(), err.position());
@@ -131,7 +149,7 @@ pub unsafe extern "C" fn questdb_conf_str_get(
 
 #[repr(C)]
 pub struct questdb_conf_str_iter {
-    inner: hash_map::Iter<'static, String, String>,
+    inner: Box<dyn Iterator<Item = (&'static str, &'static str)>>,
 }
 
 #[no_mangle]
@@ -143,7 +161,31 @@ pub unsafe extern "C" fn questdb_conf_str_iter_pairs(
     }
     let conf_str = &(*conf_str).inner;
     let iter = questdb_conf_str_iter {
-        inner: conf_str.params().iter(),
+        inner: Box::new(conf_str.params().iter()),
+    };
+    Box::into_raw(Box::new(iter))
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn questdb_conf_str_get_all(
+    conf_str: *const questdb_conf_str,
+    key: *const c_char,
+    key_len: usize,
+) -> *mut questdb_conf_str_iter {
+    if conf_str.is_null() || key.is_null() {
+        return ptr::null_mut();
+    }
+
+    let conf_str = &(*conf_str).inner;
+    let key = slice::from_raw_parts(key as *const u8, key_len);
+    // Copy the key: The caller's buffer need not outlive the iterator.
+    let key_str = match std::str::from_utf8(key) {
+        Ok(s) => s.to_string(),
+        Err(_) => return ptr::null_mut(),
+    };
+
+    let iter = questdb_conf_str_iter {
+        inner: Box::new(conf_str.params().iter().filter(move |(k, _)| *k == key_str)),
     };
     Box::into_raw(Box::new(iter))
 }
@@ -0,0 +1,285 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::{raw_offset, ConfStr, ValueError};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+use core::net::{Ipv4Addr, Ipv6Addr};
+use core::slice;
+#[cfg(feature = "std")]
+use std::io;
+#[cfg(feature = "std")]
+use std::net::{IpAddr, SocketAddr, ToSocketAddrs};
+
+const EXPECTED_HOST: &str = "a host name or IP address";
+const EXPECTED_IPV6: &str = "an IPv6 address enclosed in brackets, e.g. `[::1]:9000`";
+const EXPECTED_PORT: &str = "a port number between 1 and 65535";
+const EXPECTED_ADDRESS: &str = "a comma-separated list of addresses";
+
+/// Port used when an address omits it: 9000 for `http`/`https`
+/// and 9009 for `tcp`/`tcps`.
+pub fn default_port(service: &str) -> Option<u16> {
+    match service {
+        "http" | "https" => Some(9000),
+        "tcp" | "tcps" => Some(9009),
+        _ => None,
+    }
+}
+
+/// The host part of an [`Address`].
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub enum Host {
+    Name(String),
+    Ipv4(Ipv4Addr),
+    Ipv6(Ipv6Addr),
+}
+
+impl Display for Host {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Host::Name(name) => write!(f, "{}", name),
+            Host::Ipv4(ip) => write!(f, "{}", ip),
+            Host::Ipv6(ip) => write!(f, "[{}]", ip),
+        }
+    }
+}
+
+/// A host and port, as found in the `addr` parameter.
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
+pub struct Address {
+    host: Host,
+    port: u16,
+}
+
+impl Address {
+    /// The host name or IP address.
+    pub fn host(&self) -> &Host {
+        &self.host
+    }
+
+    /// The port, or the service's default port if omitted.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+}
+
+impl Display for Address {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.host, self.port)
+    }
+}
+
+#[cfg(feature = "std")]
+impl ToSocketAddrs for Address {
+    type Iter = vec::IntoIter<SocketAddr>;
+
+    /// Resolves host names, e.g. to connect with `TcpStream::connect`.
+    fn to_socket_addrs(&self) -> io::Result<Self::Iter> {
+        match &self.host {
+            Host::Name(name) => (name.as_str(), self.port).to_socket_addrs(),
+            Host::Ipv4(ip) => Ok(vec![SocketAddr::new(IpAddr::V4(*ip), self.port)].into_iter()),
+            Host::Ipv6(ip) => Ok(vec![SocketAddr::new(IpAddr::V6(*ip), self.port)].into_iter()),
+        }
+    }
+}
+
+/// The addresses listed in one or more `addr` parameters, in order.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct AddressList {
+    addresses: Vec<Address>,
+}
+
+impl AddressList {
+    /// Number of addresses. Never zero.
+    pub fn len(&self) -> usize {
+        self.addresses.len()
+    }
+
+    /// Always false: A list has at least one address.
+    pub fn is_empty(&self) -> bool {
+        self.addresses.is_empty()
+    }
+
+    /// Iterate over the addresses in order.
+    pub fn iter(&self) -> slice::Iter<'_, Address> {
+        self.addresses.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a AddressList {
+    type Item = &'a Address;
+    type IntoIter = slice::Iter<'a, Address>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl IntoIterator for AddressList {
+    type Item = Address;
+    type IntoIter = vec::IntoIter<Address>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.addresses.into_iter()
+    }
+}
+
+fn is_host_name(name: &str) -> bool {
+    // Letters, digits, `-` and `_` in dot-separated labels.
+    // Underscores aren't valid in DNS names, but are common in container host names.
+    // An all-digit last label is a mistyped IPv4 address, such as `999.1.1.1`
+    // (RFC 1123, section 2.1).
+    let last_label = name.rsplit('.').next().unwrap_or(name);
+    name.len() <= 253
+        && !last_label.bytes().all(|b| b.is_ascii_digit())
+        && name.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        })
+}
+
+fn parse_port(port: &str) -> Option<u16> {
+    // `u16::from_str` accepts a leading `+`.
+    match port.bytes().all(|b| b.is_ascii_digit()) {
+        true => port.parse().ok().filter(|&port| port != 0),
+        false => None,
+    }
+}
+
+/// Parse a single address. On error, returns the expected format and the
+/// byte offset within `item`.
+pub(crate) fn parse_address(
+    item: &str,
+    default_port: Option<u16>,
+) -> Result<Address, (&'static str, usize)> {
+    let (host, port_pos) = if let Some(rest) = item.strip_prefix('[') {
+        let end = rest.find(']').ok_or((EXPECTED_IPV6, 0))?;
+        let ip = rest[..end].parse().map_err(|_| (EXPECTED_IPV6, 0))?;
+        (Host::Ipv6(ip), end + 2)
+    } else {
+        let end = item.find(':').unwrap_or(item.len());
+        let host = &item[..end];
+        if item[end..].len() > 1 && item[end + 1..].contains(':') {
+            return Err((EXPECTED_IPV6, 0));
+        }
+        let host = if let Ok(ip) = host.parse() {
+            Host::Ipv4(ip)
+        } else if is_host_name(host) {
+            Host::Name(host.to_string())
+        } else {
+            return Err((EXPECTED_HOST, 0));
+        };
+        (host, end)
+    };
+    let port = match &item[port_pos..] {
+        "" => default_port.ok_or((EXPECTED_PORT, item.len()))?,
+        rest => match rest.strip_prefix(':').and_then(parse_port) {
+            Some(port) => port,
+            None if rest.starts_with(':') => return Err((EXPECTED_PORT, port_pos + 1)),
+            None => return Err((EXPECTED_PORT, port_pos)),
+        },
+    };
+    Ok(Address { host, port })
+}
+
+/// Parse a comma-separated list of addresses. On error, returns the
+/// expected format and the byte offset within `value`.
+fn parse_address_list(
+    value: &str,
+    default_port: Option<u16>,
+    addresses: &mut Vec<Address>,
+) -> Result<(), (&'static str, usize)> {
+    let mut offset = 0;
+    for item in value.split(',') {
+        let trimmed = item.trim_start();
+        let start = offset + item.len() - trimmed.len();
+        let trimmed = trimmed.trim_end();
+        if trimmed.is_empty() {
+            return Err((EXPECTED_ADDRESS, start));
+        }
+        let address =
+            parse_address(trimmed, default_port).map_err(|(expected, p)| (expected, start + p))?;
+        addresses.push(address);
+        offset += item.len() + 1;
+    }
+    Ok(())
+}
+
+impl ConfStr {
+    /// Get the addresses listed in a parameter, typically `addr`.
+    ///
+    /// Each value is a comma-separated list of `host[:port]` entries, where the
+    /// host is a name, an IPv4 address or a bracketed IPv6 address. When parsed
+    /// with [`DuplicateKeys::Collect`](crate::DuplicateKeys::Collect), the
+    /// addresses of every value are returned, in order.
+    /// A missing port defaults to the service's [`default_port`].
+    ///
+    /// Returns `Ok(None)` if the key is absent.
+    ///
+    /// ```
+    /// use questdb_confstr::{parse_conf_str, Host};
+    /// # use questdb_confstr::ValueError;
+    /// let config = parse_conf_str("http::addr=db1,10.0.0.2:9001,[::1]:9002;").unwrap();
+    /// let addresses = config.get_addresses("addr")?.unwrap();
+    /// let addresses: Vec<String> = addresses.iter().map(|a| a.to_string()).collect();
+    /// assert_eq!(addresses, ["db1:9000", "10.0.0.2:9001", "[::1]:9002"]);
+    ///
+    /// let config = parse_conf_str("tcp::addr=::1;").unwrap();
+    /// let err = config.get_addresses("addr").unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "invalid value for key \"addr\", expected an IPv6 address enclosed in brackets, \
+    ///      e.g. `[::1]:9000` at position 10");
+    /// # Ok::<(), ValueError>(())
+    /// ```
+    pub fn get_addresses(&self, key: &str) -> Result<Option<AddressList>, ValueError> {
+        let default_port = default_port(self.service());
+        let mut addresses = Vec::new();
+        for (index, (k, value)) in self.params().iter().enumerate() {
+            if k != key {
+                continue;
+            }
+            parse_address_list(value, default_port, &mut addresses).map_err(
+                |(expected, offset)| {
+                    let position = self
+                        .value_span_at(index)
+                        .map(|span| span.start + raw_offset(value, offset));
+                    ValueError::new(key, expected, position)
+                },
+            )?;
+        }
+        match addresses.is_empty() {
+            true => Ok(None),
+            false => Ok(Some(AddressList { addresses })),
+        }
+    }
+}
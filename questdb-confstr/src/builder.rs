@@ -0,0 +1,116 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::secret::wipe;
+use crate::write::{write_ident, write_value};
+use crate::{parse_err, ConfStr, ErrorKind, Key, Params, ParsingError, Value};
+use alloc::string::String;
+use alloc::vec::Vec;
+
+/// Build a [`ConfStr`] programmatically.
+///
+/// Unlike [`ConfStr::new`], the service name, keys and values are validated
+/// with the same rules as [`parse_conf_str`](crate::parse_conf_str), so the
+/// result always round-trips through [`ConfStr::to_conf_string`].
+///
+/// ```
+/// use questdb_confstr::ConfStrBuilder;
+/// # use questdb_confstr::ParsingError;
+/// let config = ConfStrBuilder::new("http")
+///     .param("addr", "localhost:9000")
+///     .param("password", "a;b")
+///     .build()?;
+/// assert_eq!(config.to_conf_string()?, "http::addr=localhost:9000;password=a;;b;");
+/// # Ok::<(), ParsingError>(())
+/// ```
+#[derive(Clone)]
+pub struct ConfStrBuilder {
+    service: String,
+    params: Vec<(Key, Value)>,
+}
+
+impl ConfStrBuilder {
+    /// Start building a configuration string for the given service.
+    pub fn new(service: impl Into<String>) -> Self {
+        ConfStrBuilder {
+            service: service.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Append a parameter.
+    pub fn param(mut self, key: impl Into<Key>, value: impl Into<Value>) -> Self {
+        self.params.push((key.into(), value.into()));
+        self
+    }
+
+    /// Validate and build the configuration string.
+    ///
+    /// The position of an error is the byte offset in the equivalent serialized
+    /// string. The `ErrorKind` describes the offending service name, key or value
+    /// on its own, so it may differ from the parser's: For the key `a-b`, this
+    /// reports `MustBeAlphanumeric('-')`, where the parser reports a bad separator.
+    pub fn build(mut self) -> Result<ConfStr, ParsingError> {
+        // Validate by serializing into a buffer large enough to never reallocate,
+        // so it can be wiped afterwards.
+        let capacity = self
+            .params
+            .iter()
+            .fold(self.service.len() + 2, |len, (k, v)| {
+                len + k.len() + 2 * v.len() + 2
+            });
+        let mut out = String::with_capacity(capacity);
+        let params = self.validate(&mut out);
+        wipe(&mut out);
+        Ok(ConfStr::new(core::mem::take(&mut self.service), params?))
+    }
+
+    fn validate(&self, out: &mut String) -> Result<Params, ParsingError> {
+        write_ident(out, &self.service)?;
+        out.push_str("::");
+        let mut params = Params::new();
+        for (key, value) in &self.params {
+            let key_pos = out.len();
+            write_ident(out, key)?;
+            if params.contains_key(key) {
+                return Err(parse_err(ErrorKind::DuplicateKey(key.clone()), key_pos));
+            }
+            out.push('=');
+            write_value(out, value)?;
+            out.push(';');
+            params.insert(key.clone(), value.clone());
+        }
+        Ok(params)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for ConfStrBuilder {
+    fn drop(&mut self) {
+        for (key, value) in self.params.iter_mut() {
+            wipe(key);
+            wipe(value);
+        }
+    }
+}
@@ -0,0 +1,365 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+//! Deserialize a configuration string into a user struct with serde.
+//!
+//! Requires the `serde` feature.
+//!
+//! ```
+//! use serde::Deserialize;
+//! # use questdb_confstr::de::Error;
+//!
+//! #[derive(Deserialize)]
+//! #[serde(deny_unknown_fields)]
+//! struct Config {
+//!     #[serde(rename = "$service")]
+//!     service: String,
+//!     addr: String,
+//!     retry_timeout: Option<u64>,
+//!     #[serde(default)]
+//!     tls_verify: bool,
+//! }
+//!
+//! let config: Config = questdb_confstr::from_str("http::addr=localhost:9000;retry_timeout=10000;")?;
+//! assert_eq!(config.service, "http");
+//! assert_eq!(config.addr, "localhost:9000");
+//! assert_eq!(config.retry_timeout, Some(10000));
+//! assert!(!config.tls_verify);
+//! # Ok::<(), Error>(())
+//! ```
+
+use crate::typed::{expected_type, parse_bool, EXPECTED_BOOL};
+use crate::{parse_conf_str, ConfStr, ParsingError, Position};
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::fmt::{Display, Formatter};
+use serde::de::value::BorrowedStrDeserializer;
+use serde::de::{
+    self, DeserializeOwned, DeserializeSeed, Expected, MapAccess, Unexpected, Visitor,
+};
+use serde::{forward_to_deserialize_any, Deserialize};
+
+/// Struct field name bound to the service name rather than to a parameter.
+///
+/// Since `$` can't appear in keys, this never clashes with a parameter.
+/// Use it with `#[serde(rename = "$service")]`.
+pub const SERVICE_FIELD: &str = "$service";
+
+/// Error deserializing a configuration string.
+///
+/// As with [`ErrorKind`](crate::ErrorKind), values are never included in the message.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Error {
+    msg: String,
+    key: Option<String>,
+    position: Option<Position>,
+}
+
+impl Error {
+    /// The key whose value could not be deserialized, if any.
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Byte position in the input string, if known.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    fn value(key: &str, msg: impl Display) -> Self {
+        Error {
+            msg: msg.to_string(),
+            key: Some(key.to_string()),
+            position: None,
+        }
+    }
+
+    fn or_position(mut self, position: Option<Position>) -> Self {
+        self.position = self.position.or(position);
+        self
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "invalid value for key {:?}, {}", key, self.msg)?,
+            None => write!(f, "{}", self.msg)?,
+        }
+        if let Some(position) = self.position {
+            write!(f, " at position {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+impl serde::de::StdError for Error {}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error {
+            msg: msg.to_string(),
+            key: None,
+            position: None,
+        }
+    }
+
+    // The default implementations below would echo the value.
+
+    fn invalid_type(_unexp: Unexpected, exp: &dyn Expected) -> Self {
+        Self::custom(format_args!("expected {}", exp))
+    }
+
+    fn invalid_value(_unexp: Unexpected, exp: &dyn Expected) -> Self {
+        Self::custom(format_args!("expected {}", exp))
+    }
+
+    fn unknown_variant(_variant: &str, expected: &'static [&'static str]) -> Self {
+        Self::custom(format_args!("expected one of {:?}", expected))
+    }
+}
+
+impl From<ParsingError> for Error {
+    fn from(err: ParsingError) -> Self {
+        Error {
+            msg: err.kind().to_string(),
+            key: None,
+            position: Some(err.position()),
+        }
+    }
+}
+
+/// Deserialize an instance of `T` from a configuration string.
+pub fn from_str<T: DeserializeOwned>(input: &str) -> Result<T, Error> {
+    let conf_str = parse_conf_str(input)?;
+    from_conf_str(&conf_str)
+}
+
+/// Deserialize an instance of `T` from a parsed configuration string.
+///
+/// String fields may borrow from the `ConfStr`.
+pub fn from_conf_str<'a, T>(conf_str: &'a ConfStr) -> Result<T, Error>
+where
+    T: Deserialize<'a>,
+{
+    T::deserialize(Deserializer::new(conf_str))
+}
+
+/// Serde deserializer over the parameters of a [`ConfStr`].
+pub struct Deserializer<'a> {
+    conf_str: &'a ConfStr,
+}
+
+impl<'a> Deserializer<'a> {
+    /// Create a deserializer for a parsed configuration string.
+    pub fn new(conf_str: &'a ConfStr) -> Self {
+        Deserializer { conf_str }
+    }
+
+    fn map_access(&self, with_service: bool) -> ParamsAccess<'a> {
+        let conf_str = self.conf_str;
+        let service = with_service.then(|| Entry {
+            key: SERVICE_FIELD,
+            value: conf_str.service(),
+            key_pos: None,
+            value_pos: conf_str.service_span().map(|span| span.start),
+        });
+        let params = conf_str.params().iter().map(move |(key, value)| Entry {
+            key,
+            value,
+            key_pos: conf_str.key_span(key).map(|span| span.start),
+            value_pos: conf_str.value_span(key).map(|span| span.start),
+        });
+        ParamsAccess {
+            entries: Box::new(service.into_iter().chain(params)),
+            current: None,
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(self.map_access(false))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_map(self.map_access(fields.contains(&SERVICE_FIELD)))
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map enum identifier ignored_any
+    }
+}
+
+struct Entry<'a> {
+    key: &'a str,
+    value: &'a str,
+    key_pos: Option<Position>,
+    value_pos: Option<Position>,
+}
+
+struct ParamsAccess<'a> {
+    entries: Box<dyn Iterator<Item = Entry<'a>> + 'a>,
+    current: Option<Entry<'a>>,
+}
+
+impl<'de> MapAccess<'de> for ParamsAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some(entry) = self.entries.next() else {
+            return Ok(None);
+        };
+        let key_pos = entry.key_pos;
+        let key = BorrowedStrDeserializer::<Error>::new(entry.key);
+        self.current = Some(entry);
+        seed.deserialize(key)
+            .map(Some)
+            .map_err(|err| err.or_position(key_pos))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let entry = self
+            .current
+            .take()
+            .expect("next_value_seed called before next_key_seed");
+        let value_pos = entry.value_pos;
+        seed.deserialize(ValueDeserializer {
+            key: entry.key,
+            value: entry.value,
+        })
+        .map_err(|err| err.or_position(value_pos))
+    }
+}
+
+/// Deserializes a single value, coercing it from a string as needed.
+struct ValueDeserializer<'a> {
+    key: &'a str,
+    value: &'a str,
+}
+
+impl ValueDeserializer<'_> {
+    fn parse<T: core::str::FromStr>(&self) -> Result<T, Error> {
+        self.value
+            .parse()
+            .map_err(|_| Error::value(self.key, format_args!("expected {}", expected_type::<T>())))
+    }
+}
+
+/// Attribute an error raised while visiting a value to its key.
+fn with_key(key: &str, err: Error) -> Error {
+    match err.key {
+        Some(_) => err,
+        None => Error::value(key, err.msg).or_position(err.position),
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                let parsed = self.parse()?;
+                visitor.$visit(parsed).map_err(|err| with_key(self.key, err))
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor
+            .visit_borrowed_str(self.value)
+            .map_err(|err| with_key(self.key, err))
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match parse_bool(self.value) {
+            Some(value) => visitor.visit_bool(value),
+            None => Err(Error::value(
+                self.key,
+                format_args!("expected {}", EXPECTED_BOOL),
+            )),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // Absent keys are `None`: A key that is present always has a value.
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor
+            .visit_enum(BorrowedStrDeserializer::new(self.value))
+            .map_err(|err| with_key(self.key, err))
+    }
+
+    forward_to_deserialize_any! {
+        str string bytes byte_buf unit unit_struct seq tuple
+        tuple_struct map struct identifier ignored_any
+    }
+}
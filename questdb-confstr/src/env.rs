@@ -0,0 +1,185 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::secret::{wipe, wipe_bytes};
+use crate::write::check_ident;
+use crate::{parse_conf_str, parse_standalone_value, ConfStr, Params, ParsingError};
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+use std::error::Error;
+
+/// Separates the base variable name from the key in per-key overrides,
+/// e.g. `QDB_CLIENT_CONF__PASSWORD`.
+pub const OVERRIDE_SEPARATOR: &str = "__";
+
+/// Error loading a configuration string from the environment.
+///
+/// Names the variable at fault, but never includes its value.
+#[derive(Debug)]
+pub enum EnvError {
+    /// The variable is not set.
+    NotPresent { variable: String },
+
+    /// The variable's value is not valid Unicode.
+    ///
+    /// To find the position of the invalid bytes, parse the value read with
+    /// [`std::env::var_os`] with [`parse_conf_str_os`](crate::parse_conf_str_os).
+    NotUnicode { variable: String },
+
+    /// The override variable's name does not map to a valid key.
+    InvalidOverrideKey { variable: String },
+
+    /// The variable's value failed to parse.
+    /// The position is relative to the variable's value.
+    Parsing {
+        variable: String,
+        error: ParsingError,
+    },
+}
+
+impl EnvError {
+    /// Name of the variable at fault.
+    pub fn variable(&self) -> &str {
+        match self {
+            EnvError::NotPresent { variable } => variable,
+            EnvError::NotUnicode { variable } => variable,
+            EnvError::InvalidOverrideKey { variable } => variable,
+            EnvError::Parsing { variable, .. } => variable,
+        }
+    }
+}
+
+impl Display for EnvError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            EnvError::NotPresent { variable } => {
+                write!(f, "environment variable {:?} is not set", variable)
+            }
+            EnvError::NotUnicode { variable } => {
+                write!(
+                    f,
+                    "environment variable {:?} is not valid unicode",
+                    variable
+                )
+            }
+            EnvError::InvalidOverrideKey { variable } => {
+                write!(f, "invalid key in environment variable {:?}", variable)
+            }
+            EnvError::Parsing { variable, error } => {
+                write!(f, "invalid environment variable {:?}: {}", variable, error)
+            }
+        }
+    }
+}
+
+impl Error for EnvError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            EnvError::Parsing { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// The variable's value, to be wiped by the caller.
+fn var(variable: &str) -> Result<String, EnvError> {
+    let Some(value) = std::env::var_os(variable) else {
+        return Err(EnvError::NotPresent {
+            variable: variable.to_string(),
+        });
+    };
+    String::from_utf8(value.into_encoded_bytes()).map_err(|err| {
+        wipe_bytes(&mut err.into_bytes());
+        EnvError::NotUnicode {
+            variable: variable.to_string(),
+        }
+    })
+}
+
+impl ConfStr {
+    /// Parse the configuration string in the environment variable `variable`,
+    /// then apply per-key overrides from the variables named
+    /// `{variable}__{KEY}`, e.g. `QDB_CLIENT_CONF__PASSWORD`.
+    ///
+    /// The key is the lowercased suffix of the override variable's name.
+    /// Override values follow the same rules as values in the configuration
+    /// string: In particular, `;` must be escaped as `;;`.
+    ///
+    /// [`ConfStr::source_of`] reports the variable that supplied each key.
+    /// If any overrides apply, spans are dropped, as for [`ConfStr::merge`].
+    ///
+    /// Requires the `std` feature.
+    pub fn from_env(variable: &str) -> Result<ConfStr, EnvError> {
+        let mut input = var(variable)?;
+        let base = parse_conf_str(&input);
+        wipe(&mut input);
+        let base = base
+            .map_err(|error| EnvError::Parsing {
+                variable: variable.to_string(),
+                error,
+            })?
+            .with_source(variable);
+
+        // Sorted, so new keys are appended in a stable order.
+        let prefix = format!("{}{}", variable, OVERRIDE_SEPARATOR);
+        let mut names: Vec<String> = std::env::vars_os()
+            .filter_map(|(name, _)| name.into_string().ok())
+            .filter(|name| name.starts_with(&prefix))
+            .collect();
+        if names.is_empty() {
+            return Ok(base);
+        }
+        names.sort();
+
+        let mut overrides = ConfStr::new(base.service().to_string(), Params::new());
+        for name in names {
+            let key = name[prefix.len()..].to_lowercase();
+            if check_ident(&key).is_err() {
+                return Err(EnvError::InvalidOverrideKey { variable: name });
+            }
+            let mut raw = var(&name)?;
+            let value = parse_standalone_value(&raw).map(|value| value.into_owned());
+            wipe(&mut raw);
+            let value = value.map_err(|error| EnvError::Parsing {
+                variable: name.clone(),
+                error,
+            })?;
+            // Variables differing only by case map to the same key: The last one wins.
+            match overrides.params.insert(key.clone(), value) {
+                Some(mut old) => {
+                    wipe(&mut old);
+                    let index = overrides.params.index_of(&key).expect("key is present");
+                    overrides.sources[index] = Some(name);
+                }
+                None => overrides.sources.push(Some(name)),
+            }
+        }
+        Ok(base
+            .overlay(&overrides)
+            .expect("overrides have the same service"))
+    }
+}
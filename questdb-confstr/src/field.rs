@@ -0,0 +1,220 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::{Position, ValueError};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+
+/// A single problem found while validating a [`ConfStr`](crate::ConfStr) against a
+/// [`Schema`](crate::Schema) or converting it into a typed struct.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum FieldError {
+    /// The service name is not one of the allowed services.
+    UnsupportedService {
+        service: String,
+        allowed: Vec<String>,
+        position: Option<Position>,
+    },
+
+    /// The key is set, but is not supported for this service.
+    UnsupportedKey {
+        key: String,
+        service: String,
+        position: Option<Position>,
+    },
+
+    /// A required key is absent.
+    MissingKey(String),
+
+    /// The value could not be converted.
+    InvalidValue(ValueError),
+}
+
+impl FieldError {
+    /// The key at fault, if the error relates to a parameter.
+    pub fn key(&self) -> Option<&str> {
+        match self {
+            FieldError::UnsupportedService { .. } => None,
+            FieldError::UnsupportedKey { key, .. } => Some(key),
+            FieldError::MissingKey(key) => Some(key),
+            FieldError::InvalidValue(err) => Some(err.key()),
+        }
+    }
+
+    /// Byte position in the input string, if the `ConfStr` was parsed.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            FieldError::UnsupportedService { position, .. } => *position,
+            FieldError::UnsupportedKey { position, .. } => *position,
+            FieldError::MissingKey(_) => None,
+            FieldError::InvalidValue(err) => err.position(),
+        }
+    }
+}
+
+impl Display for FieldError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldError::UnsupportedService {
+                service, allowed, ..
+            } => write!(
+                f,
+                "unsupported service {:?}, expected one of {:?}",
+                service, allowed
+            )?,
+            FieldError::UnsupportedKey { key, service, .. } => write!(
+                f,
+                "key {:?} is not supported for service {:?}",
+                key, service
+            )?,
+            FieldError::MissingKey(key) => return write!(f, "missing required key {:?}", key),
+            FieldError::InvalidValue(err) => return write!(f, "{}", err),
+        }
+        if let Some(position) = self.position() {
+            write!(f, " at position {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+/// Every problem found while validating or converting a [`ConfStr`](crate::ConfStr).
+///
+/// Returned by [`Schema::validate`](crate::Schema::validate) and by the
+/// `TryFrom<&ConfStr>` implementations generated by `#[derive(FromConfStr)]`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct FromConfStrError {
+    errors: Vec<FieldError>,
+}
+
+impl FromConfStrError {
+    /// The individual errors, in the order they were found.
+    pub fn errors(&self) -> &[FieldError] {
+        &self.errors
+    }
+}
+
+impl Display for FromConfStrError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (index, err) in self.errors.iter().enumerate() {
+            if index > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", err)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FromConfStrError {}
+
+/// Support code for `#[derive(FromConfStr)]`. Not public API.
+#[doc(hidden)]
+pub mod __private {
+    use super::{FieldError, FromConfStrError};
+    use crate::{ConfStr, ValueError};
+    use alloc::format;
+    use alloc::string::ToString;
+    use core::fmt::Display;
+
+    pub use alloc::vec::Vec;
+
+    pub fn check_service(errors: &mut Vec<FieldError>, conf_str: &ConfStr, allowed: &[&str]) {
+        if !allowed.contains(&conf_str.service()) {
+            errors.push(FieldError::UnsupportedService {
+                service: conf_str.service().to_string(),
+                allowed: allowed.iter().map(|s| s.to_string()).collect(),
+                position: conf_str.service_span().map(|span| span.start),
+            });
+        }
+    }
+
+    pub fn check_key_service(
+        errors: &mut Vec<FieldError>,
+        conf_str: &ConfStr,
+        key: &str,
+        allowed: &[&str],
+    ) {
+        if conf_str.get(key).is_some() && !allowed.contains(&conf_str.service()) {
+            errors.push(FieldError::UnsupportedKey {
+                key: key.to_string(),
+                service: conf_str.service().to_string(),
+                position: conf_str.key_span(key).map(|span| span.start),
+            });
+        }
+    }
+
+    pub fn parse_with<T, E: Display>(
+        conf_str: &ConfStr,
+        key: &str,
+        secret: bool,
+        parse: impl FnOnce(&str) -> Result<T, E>,
+    ) -> Result<Option<T>, ValueError> {
+        let Some(value) = conf_str.get(key) else {
+            return Ok(None);
+        };
+        parse(value).map(Some).map_err(|err| {
+            // The parser's message may quote the value: Drop it for secrets.
+            let expected = match secret {
+                true => "a valid value".to_string(),
+                false => format!("a valid value ({})", err),
+            };
+            ValueError::new(
+                key,
+                expected,
+                conf_str.value_span(key).map(|span| span.start),
+            )
+        })
+    }
+
+    pub fn field<T>(
+        errors: &mut Vec<FieldError>,
+        key: &str,
+        required: bool,
+        value: Result<Option<T>, ValueError>,
+    ) -> Option<T> {
+        match value {
+            Ok(Some(value)) => Some(value),
+            Ok(None) => {
+                if required {
+                    errors.push(FieldError::MissingKey(key.to_string()));
+                }
+                None
+            }
+            Err(err) => {
+                errors.push(FieldError::InvalidValue(err));
+                None
+            }
+        }
+    }
+
+    pub fn finish(errors: Vec<FieldError>) -> Result<(), FromConfStrError> {
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(FromConfStrError { errors }),
+        }
+    }
+}
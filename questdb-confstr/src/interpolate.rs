@@ -0,0 +1,261 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::secret::{push_str, wipe};
+use crate::{is_ident_char, raw_offset, ConfStr, Position};
+use alloc::collections::BTreeMap;
+use alloc::string::{String, ToString};
+use core::fmt;
+use core::fmt::{Display, Formatter};
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+
+/// Looks up the variables referenced by `${VAR}` in values.
+pub trait Resolver {
+    /// The value of the variable, or `None` if it is not set.
+    fn resolve(&self, name: &str) -> Option<String>;
+}
+
+/// Resolves variables from the process environment. Requires the `std` feature.
+///
+/// Variables whose value is not valid Unicode are treated as unset.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvResolver;
+
+#[cfg(feature = "std")]
+impl Resolver for EnvResolver {
+    fn resolve(&self, name: &str) -> Option<String> {
+        std::env::var(name).ok()
+    }
+}
+
+#[cfg(feature = "std")]
+impl Resolver for HashMap<String, String> {
+    fn resolve(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+impl Resolver for BTreeMap<String, String> {
+    fn resolve(&self, name: &str) -> Option<String> {
+        self.get(name).cloned()
+    }
+}
+
+/// Error expanding `${VAR}` references in a value.
+///
+/// Neither the value nor the resolved variables are included in the message.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum InterpolationError {
+    /// The variable is not set and no default was given.
+    UnsetVariable {
+        key: String,
+        variable: String,
+        position: Option<Position>,
+    },
+
+    /// A `${` without a closing `}`.
+    Unterminated {
+        key: String,
+        position: Option<Position>,
+    },
+
+    /// The variable name is empty or contains characters other than
+    /// alphanumerics and `_`.
+    InvalidVariableName {
+        key: String,
+        position: Option<Position>,
+    },
+}
+
+impl InterpolationError {
+    /// The key whose value could not be expanded.
+    pub fn key(&self) -> &str {
+        match self {
+            InterpolationError::UnsetVariable { key, .. } => key,
+            InterpolationError::Unterminated { key, .. } => key,
+            InterpolationError::InvalidVariableName { key, .. } => key,
+        }
+    }
+
+    /// Byte position of the offending `$` in the input string, if the `ConfStr` was parsed.
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            InterpolationError::UnsetVariable { position, .. } => *position,
+            InterpolationError::Unterminated { position, .. } => *position,
+            InterpolationError::InvalidVariableName { position, .. } => *position,
+        }
+    }
+}
+
+impl Display for InterpolationError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InterpolationError::UnsetVariable { key, variable, .. } => write!(
+                f,
+                "unset variable {:?} in value for key {:?}",
+                variable, key
+            )?,
+            InterpolationError::Unterminated { key, .. } => {
+                write!(f, "unterminated \"${{\" in value for key {:?}", key)?
+            }
+            InterpolationError::InvalidVariableName { key, .. } => {
+                write!(f, "invalid variable name in value for key {:?}", key)?
+            }
+        }
+        if let Some(position) = self.position() {
+            write!(f, " at position {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for InterpolationError {}
+
+enum ErrorKind {
+    UnsetVariable(String),
+    Unterminated,
+    InvalidVariableName,
+}
+
+/// Expand a single (unescaped) value into `out`.
+///
+/// On error, returns the byte offset of the offending `$` in the value.
+fn expand(
+    value: &str,
+    resolver: &impl Resolver,
+    out: &mut String,
+) -> Result<(), (ErrorKind, usize)> {
+    let mut rest = value;
+    while let Some(index) = rest.find('$') {
+        push_str(out, &rest[..index]);
+        let offset = value.len() - rest.len() + index;
+        let after = &rest[index + 1..];
+        let consumed = if after.starts_with('$') {
+            push_str(out, "$");
+            2
+        } else if let Some(body) = after.strip_prefix('{') {
+            let end = body.find('}').ok_or((ErrorKind::Unterminated, offset))?;
+            let (name, default) = match body[..end].split_once(":-") {
+                Some((name, default)) => (name, Some(default)),
+                None => (&body[..end], None),
+            };
+            if name.is_empty() || !name.chars().all(is_ident_char) {
+                return Err((ErrorKind::InvalidVariableName, offset));
+            }
+            let mut resolved = resolver.resolve(name);
+            // As in the shell, `:-` also substitutes the default for empty variables.
+            match (resolved.as_deref(), default) {
+                (Some(""), Some(default)) | (None, Some(default)) => push_str(out, default),
+                (Some(resolved), _) => push_str(out, resolved),
+                (None, None) => {
+                    return Err((ErrorKind::UnsetVariable(name.to_string()), offset));
+                }
+            }
+            if let Some(resolved) = resolved.as_mut() {
+                wipe(resolved);
+            }
+            end + 3
+        } else {
+            // A `$` not followed by `{` or `$` is kept as is.
+            push_str(out, "$");
+            1
+        };
+        rest = &rest[index + consumed..];
+    }
+    push_str(out, rest);
+    Ok(())
+}
+
+impl ConfStr {
+    /// Expand `${VAR}` and `${VAR:-default}` references in values,
+    /// resolving variables from the environment.
+    ///
+    /// See [`ConfStr::interpolate_with`]. Requires the `std` feature.
+    #[cfg(feature = "std")]
+    pub fn interpolate(&self) -> Result<ConfStr, InterpolationError> {
+        self.interpolate_with(&EnvResolver)
+    }
+
+    /// Expand `${VAR}` and `${VAR:-default}` references in values.
+    ///
+    /// Expansion applies to the unescaped values, so a resolved variable may
+    /// contain `;` without breaking the configuration string.
+    /// `$$` stands for a literal `$`. The default is taken literally up to
+    /// the first `}` and is used if the variable is unset or empty.
+    ///
+    /// ```
+    /// use questdb_confstr::parse_conf_str;
+    /// use std::collections::HashMap;
+    ///
+    /// let vars = HashMap::from([("PASSWORD".to_string(), "a;b".to_string())]);
+    /// let config = parse_conf_str(
+    ///     "http::password=${PASSWORD};user=${USER_NAME:-admin};price=$$5;").unwrap();
+    /// let config = config.interpolate_with(&vars).unwrap();
+    /// assert_eq!(config.get("password"), Some("a;b"));
+    /// assert_eq!(config.get("user"), Some("admin"));
+    /// assert_eq!(config.get("price"), Some("$5"));
+    /// assert_eq!(config.to_conf_string().unwrap(),
+    ///     "http::password=a;;b;user=admin;price=$5;");
+    /// ```
+    pub fn interpolate_with(
+        &self,
+        resolver: &impl Resolver,
+    ) -> Result<ConfStr, InterpolationError> {
+        let mut conf_str = self.clone();
+        for (index, (key, value)) in self.params().iter().enumerate() {
+            let mut expanded = String::with_capacity(value.len());
+            let result = expand(value, resolver, &mut expanded);
+            let changed = result.is_ok() && expanded != value;
+            if !changed {
+                wipe(&mut expanded);
+            }
+            result.map_err(|(kind, offset)| {
+                let position = self
+                    .value_span_at(index)
+                    .map(|span| span.start + raw_offset(value, offset));
+                let key = key.to_string();
+                match kind {
+                    ErrorKind::UnsetVariable(variable) => InterpolationError::UnsetVariable {
+                        key,
+                        variable,
+                        position,
+                    },
+                    ErrorKind::Unterminated => InterpolationError::Unterminated { key, position },
+                    ErrorKind::InvalidVariableName => {
+                        InterpolationError::InvalidVariableName { key, position }
+                    }
+                }
+            })?;
+            if changed {
+                // By index rather than key: Keys may repeat with `DuplicateKeys::Collect`.
+                let mut old = conf_str.params.replace_at(index, expanded);
+                wipe(&mut old);
+            }
+        }
+        Ok(conf_str)
+    }
+}
@@ -23,14 +23,76 @@
  ******************************************************************************/
 
 #![doc = include_str!("../README.md")]
+#![no_std]
+
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 
 use crate::peekable2::{Peekable2, Peekable2Ext};
-use std::collections::HashMap;
-use std::fmt;
-use std::fmt::{Debug, Display, Formatter};
-use std::str::CharIndices;
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Debug, Display, Formatter};
+use core::ops::Range;
+use core::str::CharIndices;
+#[cfg(feature = "std")]
+use std::ffi::OsStr;
 
+mod address;
+mod builder;
+#[cfg(feature = "serde")]
+pub mod de;
+#[cfg(feature = "std")]
+mod env;
+mod field;
+mod interpolate;
+mod location;
+mod merge;
+mod options;
+mod params;
 mod peekable2;
+mod redact;
+mod render;
+mod repair;
+mod schema;
+mod secret;
+#[cfg(feature = "serde")]
+pub mod ser;
+mod typed;
+mod url;
+mod write;
+
+pub use address::{default_port, Address, AddressList, Host};
+pub use builder::ConfStrBuilder;
+#[cfg(feature = "serde")]
+pub use de::{from_conf_str, from_str};
+#[cfg(feature = "std")]
+pub use env::{EnvError, OVERRIDE_SEPARATOR};
+#[doc(hidden)]
+pub use field::__private;
+pub use field::{FieldError, FromConfStrError};
+#[cfg(feature = "std")]
+pub use interpolate::EnvResolver;
+pub use interpolate::{InterpolationError, Resolver};
+pub use location::Location;
+pub use merge::MergeError;
+pub use options::{DuplicateKeys, ParseOptions};
+pub use params::{Params, ParamsIter};
+pub use redact::{Mask, Redacted, DEFAULT_SENSITIVE_KEYS};
+pub use render::RenderedError;
+pub use repair::{repair, Fix};
+pub use schema::{Schema, ServiceSchema, ValueType};
+pub use secret::Secret;
+#[cfg(feature = "serde")]
+pub use ser::to_string;
+pub use typed::ValueError;
+pub use url::{UrlError, UrlErrorKind};
+
+/// Derive `TryFrom<&ConfStr>` for a struct. Requires the `derive` feature.
+#[cfg(feature = "derive")]
+pub use questdb_confstr_derive::FromConfStr;
 
 /// Parameter keys are ascii lowercase strings.
 pub type Key = String;
@@ -38,18 +100,39 @@ pub type Key = String;
 /// Parameter values are strings.
 pub type Value = String;
 
-/// Parameters are stored in a `Vec` of `(Key, Value)` pairs.
-/// Keys are always lowercase.
-pub type Params = HashMap<Key, Value>;
+/// Byte range in the input string.
+pub type Span = Range<Position>;
+
+/// Source byte ranges of a parsed configuration string.
+#[derive(Clone, Debug, Default)]
+struct Spans {
+    service: Span,
+
+    /// Key and raw (escaped) value spans, in the same order as the params.
+    params: Vec<(Span, Span)>,
+}
 
 /// Parsed configuration string.
 ///
-/// The parameters are stored in a `Vec` of `(Key, Value)` pairs.
+/// The parameters are stored in the order they appear in the input.
+#[derive(Clone)]
 pub struct ConfStr {
     service: String,
     params: Params,
+    spans: Option<Spans>,
+    /// Name of the layer that supplied each param, in the same order as the params.
+    sources: Vec<Option<String>>,
 }
 
+impl PartialEq for ConfStr {
+    fn eq(&self, other: &Self) -> bool {
+        // Spans and sources are not part of the value: The same config may come from different inputs.
+        self.service == other.service && self.params == other.params
+    }
+}
+
+impl Eq for ConfStr {}
+
 impl Debug for ConfStr {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         // Params are hidden from debug output in case they contain sensitive information.
@@ -59,8 +142,16 @@ impl Debug for ConfStr {
 
 impl ConfStr {
     /// Create a new configuration string object.
+    ///
+    /// The service name and parameters are not validated:
+    /// Use [`ConfStrBuilder`] to enforce the parser's rules.
     pub fn new(service: String, params: Params) -> Self {
-        ConfStr { service, params }
+        ConfStr {
+            service,
+            params,
+            spans: None,
+            sources: Vec::new(),
+        }
     }
 
     /// Access the service name.
@@ -68,7 +159,7 @@ impl ConfStr {
         &self.service
     }
 
-    /// Access the parameters.
+    /// Access the parameters, in the order they appear in the input.
     pub fn params(&self) -> &Params {
         &self.params
     }
@@ -76,7 +167,202 @@ impl ConfStr {
     /// Get a parameter.
     /// Key should always be specified as lowercase.
     pub fn get(&self, key: &str) -> Option<&str> {
-        self.params.get(key).map(|s| s.as_str())
+        self.params.get(key)
+    }
+
+    /// Iterate over every value of a key, in the order they appear in the input.
+    ///
+    /// Keys only have several values when parsed with [`DuplicateKeys::Collect`].
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.params.get_all(key)
+    }
+
+    /// Byte range of the service name in the parsed input.
+    ///
+    /// Spans are only available if the `ConfStr` was parsed.
+    pub fn service_span(&self) -> Option<Span> {
+        self.spans.as_ref().map(|spans| spans.service.clone())
+    }
+
+    /// Byte range of a key in the parsed input.
+    pub fn key_span(&self, key: &str) -> Option<Span> {
+        self.param_spans(key).map(|(key_span, _)| key_span.clone())
+    }
+
+    /// Byte range of a value in the parsed input.
+    ///
+    /// The range covers the raw value, i.e. escaped `;;` count as two bytes.
+    pub fn value_span(&self, key: &str) -> Option<Span> {
+        self.param_spans(key)
+            .map(|(_, value_span)| value_span.clone())
+    }
+
+    fn param_spans(&self, key: &str) -> Option<&(Span, Span)> {
+        let index = self.params.index_of(key)?;
+        self.spans.as_ref()?.params.get(index)
+    }
+
+    /// Key span of the param at `index`, counting repeated keys.
+    pub(crate) fn key_span_at(&self, index: usize) -> Option<Span> {
+        let (key_span, _) = self.spans.as_ref()?.params.get(index)?;
+        Some(key_span.clone())
+    }
+
+    /// Value span of the param at `index`, counting repeated keys.
+    pub(crate) fn value_span_at(&self, index: usize) -> Option<Span> {
+        let (_, value_span) = self.spans.as_ref()?.params.get(index)?;
+        Some(value_span.clone())
+    }
+
+    /// Serialize back into a configuration string.
+    ///
+    /// Semicolons in values are escaped as `;;` and every parameter is
+    /// terminated by `;`, so the output parses back to an equal `ConfStr`.
+    /// Repeated keys, as kept by [`DuplicateKeys::Collect`], are written once
+    /// per value: These only parse back with [`parse_conf_str_with`] and
+    /// `Collect`, as [`parse_conf_str`] rejects them as duplicates.
+    ///
+    /// Since `ConfStr::new` accepts any strings, the service, keys and values
+    /// are validated against the same rules as the parser. On error, the position
+    /// is the byte offset in the output where parsing would have failed, but the
+    /// error kind may differ, as for [`ConfStrBuilder::build`].
+    ///
+    /// ```
+    /// use questdb_confstr::{parse_conf_str, ConfStr, Params};
+    /// # use questdb_confstr::ParsingError;
+    /// let mut params = Params::new();
+    /// params.insert("host".to_string(), "localhost".to_string());
+    /// params.insert("password".to_string(), "a;b".to_string());
+    /// let config = ConfStr::new("http".to_string(), params);
+    /// let serialized = config.to_conf_string()?;
+    /// assert_eq!(serialized, "http::host=localhost;password=a;;b;");
+    /// assert_eq!(parse_conf_str(&serialized)?, config);
+    /// # Ok::<(), ParsingError>(())
+    /// ```
+    pub fn to_conf_string(&self) -> Result<String, ParsingError> {
+        // Reserve enough for every `;` to be escaped, so secrets aren't left
+        // behind in reallocated buffers.
+        let capacity = self
+            .params
+            .iter()
+            .fold(self.service.len() + 2, |len, (k, v)| {
+                len + k.len() + 2 * v.len() + 2
+            });
+        let mut out = String::with_capacity(capacity);
+        write::write_conf_str(&mut out, &self.service, self.params.iter())?;
+        Ok(out)
+    }
+}
+
+/// Parsed configuration string borrowing from the input.
+///
+/// The service name and keys are slices of the input.
+/// Values are borrowed too, unless they contained `;;` escapes
+/// that had to be unescaped into an owned string.
+///
+/// Returned by [`parse_conf_str_ref`]. Convert into an owned [`ConfStr`]
+/// with [`ConfStrRef::into_owned`].
+#[derive(Clone)]
+pub struct ConfStrRef<'a> {
+    service: &'a str,
+    params: Vec<(&'a str, Cow<'a, str>)>,
+    spans: Spans,
+}
+
+impl PartialEq for ConfStrRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.service == other.service && self.params == other.params
+    }
+}
+
+impl Eq for ConfStrRef<'_> {}
+
+impl Debug for ConfStrRef<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Params are hidden from debug output in case they contain sensitive information.
+        write!(f, "ConfStrRef {{ service: {:?}, .. }}", self.service)
+    }
+}
+
+impl<'a> ConfStrRef<'a> {
+    /// Access the service name.
+    pub fn service(&self) -> &'a str {
+        self.service
+    }
+
+    /// Access the parameters, in the order they appear in the input.
+    pub fn params(&self) -> &[(&'a str, Cow<'a, str>)] {
+        &self.params
+    }
+
+    /// Get a parameter.
+    ///
+    /// This is a linear scan: Configuration strings only have a handful of keys.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.index_of(key)
+            .map(|index| self.params[index].1.as_ref())
+    }
+
+    /// Iterate over every value of a key, in the order they appear in the input.
+    pub fn get_all<'b>(&'b self, key: &'b str) -> impl Iterator<Item = &'b str> + 'b {
+        self.params
+            .iter()
+            .filter(move |(k, _)| *k == key)
+            .map(|(_, value)| value.as_ref())
+    }
+
+    fn index_of(&self, key: &str) -> Option<usize> {
+        self.params.iter().position(|(k, _)| *k == key)
+    }
+
+    /// Byte range of the service name in the input.
+    pub fn service_span(&self) -> Span {
+        self.spans.service.clone()
+    }
+
+    /// Byte range of a key in the input.
+    pub fn key_span(&self, key: &str) -> Option<Span> {
+        self.index_of(key)
+            .map(|index| self.spans.params[index].0.clone())
+    }
+
+    /// Byte range of a raw (escaped) value in the input.
+    pub fn value_span(&self, key: &str) -> Option<Span> {
+        self.index_of(key)
+            .map(|index| self.spans.params[index].1.clone())
+    }
+
+    /// Convert into an owned [`ConfStr`], keeping the spans.
+    pub fn into_owned(mut self) -> ConfStr {
+        let mut params = Params::new();
+        for (key, value) in core::mem::take(&mut self.params) {
+            // Repeated keys are only present when parsed with `DuplicateKeys::Collect`.
+            params.append(key.to_string(), value.into_owned());
+        }
+        ConfStr {
+            service: self.service.to_string(),
+            params,
+            spans: Some(core::mem::take(&mut self.spans)),
+            sources: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for ConfStrRef<'_> {
+    fn drop(&mut self) {
+        // Borrowed values belong to the caller's input: Only wipe the unescaped copies.
+        for (_, value) in self.params.iter_mut() {
+            if let Cow::Owned(value) = value {
+                secret::wipe(value);
+            }
+        }
+    }
+}
+
+impl From<ConfStrRef<'_>> for ConfStr {
+    fn from(conf_str: ConfStrRef<'_>) -> Self {
+        conf_str.into_owned()
     }
 }
 
@@ -93,6 +379,7 @@ pub enum ErrorKind {
     IncompleteKeyValue,
     InvalidCharInValue(char),
     DuplicateKey(String),
+    InvalidUtf8,
 }
 
 impl<'a> PartialEq<&'a ErrorKind> for ErrorKind {
@@ -130,6 +417,42 @@ impl Display for ErrorKind {
             }
             ErrorKind::InvalidCharInValue(c) => write!(f, "invalid char {:?} in value", c),
             ErrorKind::DuplicateKey(s) => write!(f, "duplicate key {:?}", s),
+            ErrorKind::InvalidUtf8 => write!(f, "invalid UTF-8 sequence"),
+        }
+    }
+}
+
+/// A likely fix for a parsing error, for a common mistake.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Hint {
+    /// A single `:` or a `;` where the service name should be followed
+    /// by `::`.
+    DoubleColon,
+
+    /// A key failed to parse right after a `;` and runs to the next `;`
+    /// without a `=`: It may be the rest of a value containing a lone `;`.
+    EscapeSemicolon,
+
+    /// The input looks like a URL, such as `http://localhost:9000`.
+    LooksLikeUrl,
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Hint::DoubleColon => write!(
+                f,
+                "did you mean `::`? Separate the service name from the parameters \
+                 with `::`, e.g. `http::addr=localhost:9000;`"
+            ),
+            Hint::EscapeSemicolon => {
+                write!(f, "if the previous value contains a `;`, escape it as `;;`")
+            }
+            Hint::LooksLikeUrl => write!(
+                f,
+                "this looks like a URL: write `http::addr=localhost:9000;` instead, \
+                 or convert it with `ConfStr::from_url`"
+            ),
         }
     }
 }
@@ -139,6 +462,7 @@ impl Display for ErrorKind {
 pub struct ParsingError {
     kind: ErrorKind,
     position: usize,
+    hint: Option<Hint>,
 }
 
 impl ParsingError {
@@ -151,10 +475,26 @@ impl ParsingError {
     pub fn kind(&self) -> &ErrorKind {
         &self.kind
     }
+
+    /// A likely fix, if the error matches a common mistake.
+    ///
+    /// Not included in the `Display` output: See [`ParsingError::render`].
+    pub fn hint(&self) -> Option<Hint> {
+        self.hint
+    }
+
+    fn with_hint(mut self, hint: Hint) -> Self {
+        self.hint = Some(hint);
+        self
+    }
 }
 
 fn parse_err(kind: ErrorKind, position: Position) -> ParsingError {
-    ParsingError { kind, position }
+    ParsingError {
+        kind,
+        position,
+        hint: None,
+    }
 }
 
 impl Display for ParsingError {
@@ -163,20 +503,41 @@ impl Display for ParsingError {
     }
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for ParsingError {}
 
-fn parse_ident(
-    iter: &mut Peekable2<CharIndices>,
+/// Validate raw input as UTF-8, reporting the position of the first invalid byte.
+pub(crate) fn from_utf8(input: &[u8]) -> Result<&str, ParsingError> {
+    core::str::from_utf8(input).map_err(|err| parse_err(ErrorKind::InvalidUtf8, err.valid_up_to()))
+}
+
+/// Chars allowed in service names and keys.
+pub(crate) fn is_ident_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || c == '_'
+}
+
+/// Control chars are never allowed in values.
+pub(crate) fn is_invalid_value_char(c: char) -> bool {
+    matches!(c, '\u{0}'..='\u{1f}' | '\u{7f}'..='\u{9f}')
+}
+
+/// Byte position of the next char, or the input length once exhausted.
+fn peek_pos(iter: &mut Peekable2<CharIndices>, input_len: usize) -> Position {
+    iter.peek0().map(|(p, _)| *p).unwrap_or(input_len)
+}
+
+fn parse_ident<'a>(
+    input: &'a str,
+    iter: &mut Peekable2<CharIndices<'a>>,
     next_pos: &mut Position,
-) -> Result<Key, ParsingError> {
-    let mut token = String::new();
+) -> Result<&'a str, ParsingError> {
+    let start = peek_pos(iter, input.len());
     while let Some((pos, c)) = iter.peek0() {
         *next_pos = *pos;
-        if c.is_ascii_alphanumeric() || *c == '_' {
-            token.push(*c);
+        if is_ident_char(*c) {
             iter.next();
         } else {
-            if token.is_empty() {
+            if *pos == start {
                 return Err(parse_err(ErrorKind::ExpectedIdentifierNot(*c), *next_pos));
             } else if !c.is_ascii() || matches!(c, '\0'..=' ') {
                 return Err(parse_err(ErrorKind::MustBeAlphanumeric(*c), *next_pos));
@@ -185,18 +546,38 @@ fn parse_ident(
         }
     }
 
-    if token.is_empty() {
+    let end = peek_pos(iter, input.len());
+    if start == end {
         return Err(parse_err(ErrorKind::ExpectedIdentifierNotEmpty, *next_pos));
     }
 
-    Ok(token)
+    Ok(&input[start..end])
 }
 
-fn parse_value(
-    iter: &mut Peekable2<CharIndices>,
+/// Map a byte offset in an unescaped value to the offset in the raw (escaped) value.
+pub(crate) fn raw_offset(value: &str, offset: usize) -> usize {
+    offset + value[..offset].matches(';').count()
+}
+
+/// Replace each `;;` escape with `;`.
+fn unescape_value(raw: &str) -> String {
+    let mut value = String::with_capacity(raw.len());
+    for (index, part) in raw.split(";;").enumerate() {
+        if index > 0 {
+            value.push(';');
+        }
+        value.push_str(part);
+    }
+    value
+}
+
+fn parse_value<'a>(
+    input: &'a str,
+    iter: &mut Peekable2<CharIndices<'a>>,
     next_pos: &mut Position,
-) -> Result<Value, ParsingError> {
-    let mut value = String::new();
+) -> Result<(Cow<'a, str>, Span), ParsingError> {
+    let start = peek_pos(iter, input.len());
+    let mut escaped = false;
     loop {
         let c1 = iter.peek0().cloned();
         let c2 = iter.peek1().cloned();
@@ -207,19 +588,38 @@ fn parse_value(
             (Some((_, ';')), Some((_, ';'))) => {
                 let _ = iter.next();
                 let _ = iter.next();
-                value.push(';');
+                escaped = true;
             }
             (Some((_, ';')), _) => break,
             (Some((p, c)), _) => {
-                if matches!(c, '\u{0}'..='\u{1f}' | '\u{7f}'..='\u{9f}') {
+                if is_invalid_value_char(c) {
                     return Err(parse_err(ErrorKind::InvalidCharInValue(c), p));
                 }
-                value.push(c);
                 let _ = iter.next();
             }
             (None, _) => break,
         }
     }
+    let span = start..peek_pos(iter, input.len());
+    let raw = &input[span.clone()];
+    if escaped {
+        Ok((Cow::Owned(unescape_value(raw)), span))
+    } else {
+        Ok((Cow::Borrowed(raw), span))
+    }
+}
+
+/// Parse a value on its own, e.g. one supplied outside of a configuration string.
+///
+/// The whole input must be the value: An unescaped `;` is an error.
+#[cfg(feature = "std")]
+pub(crate) fn parse_standalone_value(input: &str) -> Result<Cow<'_, str>, ParsingError> {
+    let mut iter = input.char_indices().peekable2();
+    let mut next_pos = 0;
+    let (value, span) = parse_value(input, &mut iter, &mut next_pos)?;
+    if span.end < input.len() {
+        return Err(parse_err(ErrorKind::InvalidCharInValue(';'), span.end));
+    }
     Ok(value)
 }
 
@@ -227,43 +627,212 @@ fn parse_double_colon(
     iter: &mut Peekable2<CharIndices>,
     next_pos: &mut Position,
 ) -> Result<bool, ParsingError> {
-    let c1 = iter.next();
-    let c2 = iter.next();
-    match (c1, c2) {
+    // Peek rather than consume, so a recovering parse can resync from the bad char.
+    match (iter.peek0().copied(), iter.peek1().copied()) {
         (Some((_, ':')), Some((_, ':'))) => {
+            iter.next();
+            iter.next();
             *next_pos += 2;
             Ok(true)
         }
-        (None, None) => Ok(false),
-        (Some((_, ':')), Some((p, c))) => Err(parse_err(ErrorKind::BadSeparator((':', c)), p)),
+        (None, _) => Ok(false),
+        (Some((_, ':')), Some((p, '/'))) => {
+            Err(parse_err(ErrorKind::BadSeparator((':', '/')), p).with_hint(Hint::LooksLikeUrl))
+        }
+        (Some((_, ':')), Some((p, c))) | (Some((p, c @ (':' | ';'))), _) => {
+            Err(parse_err(ErrorKind::BadSeparator((':', c)), p).with_hint(Hint::DoubleColon))
+        }
+        // E.g. a `-` or `.` in the service name: Not a missing `::`.
         (Some((p, c)), _) => Err(parse_err(ErrorKind::BadSeparator((':', c)), p)),
-        (None, _) => unreachable!("peekable2 guarantees that the second item is always None"),
     }
 }
 
-fn parse_params(
-    iter: &mut Peekable2<CharIndices>,
+/// A key that fails to parse right after a `;` may be the rest of a value
+/// containing a lone `;`, but only if it runs to the next `;` without a `=`:
+/// A typo like `po rt=1` is still a parameter of its own.
+fn hint_escape(input: &str, key_pos: Position, err: ParsingError) -> ParsingError {
+    let rest = &input[key_pos..];
+    let segment = &rest[..rest.find(';').unwrap_or(rest.len())];
+    let continues_value = input[..key_pos].ends_with(';')
+        && !segment.is_empty()
+        && !segment.contains(|c| c == '=' || is_invalid_value_char(c));
+    match continues_value {
+        true => err.with_hint(Hint::EscapeSemicolon),
+        false => err,
+    }
+}
+
+fn parse_param<'a>(
+    input: &'a str,
+    iter: &mut Peekable2<CharIndices<'a>>,
     next_pos: &mut Position,
-    input_len: usize,
-) -> Result<Params, ParsingError> {
-    let mut params = Params::new();
-    while let Some((p, _)) = iter.peek0() {
-        *next_pos = *p;
-        let key_pos = *next_pos;
-        let key = parse_ident(iter, next_pos)?;
-        if params.contains_key(&key) {
-            return Err(parse_err(ErrorKind::DuplicateKey(key.clone()), key_pos));
+    params: &mut Vec<(&'a str, Cow<'a, str>)>,
+    spans: &mut Vec<(Span, Span)>,
+    duplicate_keys: DuplicateKeys,
+) -> Result<(), ParsingError> {
+    let key_pos = *next_pos;
+    let key = parse_ident(input, iter, next_pos).map_err(|err| hint_escape(input, key_pos, err))?;
+    let existing = params.iter().position(|(k, _)| *k == key);
+    if existing.is_some() && duplicate_keys == DuplicateKeys::Error {
+        return Err(parse_err(ErrorKind::DuplicateKey(key.to_string()), key_pos));
+    }
+    match iter.peek0().copied() {
+        Some((p, '=')) => {
+            iter.next();
+            *next_pos = p + 1;
+        }
+        Some((p, c)) => {
+            let err = parse_err(ErrorKind::BadSeparator(('=', c)), p);
+            return Err(hint_escape(input, key_pos, err));
+        }
+        None => {
+            let err = parse_err(ErrorKind::IncompleteKeyValue, input.len());
+            return Err(hint_escape(input, key_pos, err));
         }
-        match iter.next() {
-            Some((p, '=')) => *next_pos = p + 1,
-            Some((p, c)) => return Err(parse_err(ErrorKind::BadSeparator(('=', c)), p)),
-            None => return Err(parse_err(ErrorKind::IncompleteKeyValue, input_len)),
+    }
+    let (value, value_span) = parse_value(input, iter, next_pos)?;
+    iter.next(); // skip ';', if present.
+    let param_spans = (key_pos..key_pos + key.len(), value_span);
+    match (existing, duplicate_keys) {
+        (Some(index), DuplicateKeys::LastWins) => {
+            if let Cow::Owned(mut old) = core::mem::replace(&mut params[index].1, value) {
+                secret::wipe(&mut old);
+            }
+            spans[index] = param_spans;
+        }
+        _ => {
+            params.push((key, value));
+            spans.push(param_spans);
         }
-        let value = parse_value(iter, next_pos)?;
-        iter.next(); // skip ';', if present.
-        params.insert(key, value);
     }
-    Ok(params)
+    Ok(())
+}
+
+/// Skip past the next unescaped `;`, where the following param starts.
+fn skip_to_next_param(iter: &mut Peekable2<CharIndices>) {
+    while let Some((_, c)) = iter.next() {
+        if c == ';' {
+            if let Some((_, ';')) = iter.peek0() {
+                iter.next();
+            } else {
+                break;
+            }
+        }
+    }
+}
+
+/// When recovering, collect the error and resync at the next param.
+/// Otherwise, bail out with the error.
+fn recover(
+    errors: Option<&mut Vec<ParsingError>>,
+    iter: &mut Peekable2<CharIndices>,
+    err: ParsingError,
+) -> Result<(), ParsingError> {
+    match errors {
+        Some(errors) => {
+            errors.push(err);
+            skip_to_next_param(iter);
+            Ok(())
+        }
+        None => Err(err),
+    }
+}
+
+/// Parse, either stopping at the first error,
+/// or collecting all errors if `errors` is provided.
+fn parse<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+    mut errors: Option<&mut Vec<ParsingError>>,
+) -> Result<ConfStrRef<'a>, ParsingError> {
+    let mut iter = input.char_indices().peekable2();
+    let mut next_pos = 0;
+    // Build in place, so values parsed before an error are dropped (and wiped) with it.
+    let mut conf_str = ConfStrRef {
+        service: "",
+        params: Vec::new(),
+        spans: Spans::default(),
+    };
+    let service = match parse_ident(input, &mut iter, &mut next_pos) {
+        Ok(service) => service,
+        Err(err) => {
+            recover(errors.as_deref_mut(), &mut iter, err)?;
+            ""
+        }
+    };
+    conf_str.service = service;
+    conf_str.spans.service = 0..service.len();
+    let has_separator = service.is_empty()
+        || match parse_double_colon(&mut iter, &mut next_pos) {
+            Ok(has_separator) => has_separator,
+            Err(err) => {
+                recover(errors.as_deref_mut(), &mut iter, err)?;
+                true
+            }
+        };
+    if has_separator {
+        while let Some((p, _)) = iter.peek0() {
+            next_pos = *p;
+            if let Err(err) = parse_param(
+                input,
+                &mut iter,
+                &mut next_pos,
+                &mut conf_str.params,
+                &mut conf_str.spans.params,
+                options.get_duplicate_keys(),
+            ) {
+                recover(errors.as_deref_mut(), &mut iter, err)?;
+            }
+        }
+    }
+    Ok(conf_str)
+}
+
+/// Parse a config string without copying the service name, keys and values.
+///
+/// ```
+/// use questdb_confstr::parse_conf_str_ref;
+/// # use questdb_confstr::ParsingError;
+/// let config = parse_conf_str_ref("service::key1=value1;key2=a;;b;")?;
+/// assert_eq!(config.service(), "service");
+/// assert_eq!(config.get("key1"), Some("value1"));
+/// assert_eq!(config.get("key2"), Some("a;b"));
+/// # Ok::<(), ParsingError>(())
+/// ```
+pub fn parse_conf_str_ref(input: &str) -> Result<ConfStrRef<'_>, ParsingError> {
+    parse(input, &ParseOptions::default(), None)
+}
+
+/// Same as [`parse_conf_str_ref`], with options.
+pub fn parse_conf_str_ref_with<'a>(
+    input: &'a str,
+    options: &ParseOptions,
+) -> Result<ConfStrRef<'a>, ParsingError> {
+    parse(input, options, None)
+}
+
+/// Parse a config string, reporting every error rather than just the first.
+///
+/// After each error, parsing resumes after the next unescaped `;`.
+/// Returns the parameters that parsed cleanly, along with all the errors.
+/// If the service name itself is invalid, the returned service name is empty.
+///
+/// ```
+/// use questdb_confstr::{parse_conf_str_recovering, ErrorKind};
+/// let (config, errors) = parse_conf_str_recovering("http::host=a;po rt=1;port=9000;host=b;");
+/// assert_eq!(config.service(), "http");
+/// assert_eq!(config.get("host"), Some("a"));
+/// assert_eq!(config.get("port"), Some("9000"));
+/// assert_eq!(errors.len(), 2);
+/// assert_eq!(errors[0].kind(), ErrorKind::MustBeAlphanumeric(' '));
+/// assert_eq!(errors[1].kind(), ErrorKind::DuplicateKey("host".to_string()));
+/// ```
+pub fn parse_conf_str_recovering(input: &str) -> (ConfStr, Vec<ParsingError>) {
+    let mut errors = Vec::new();
+    let conf_str = parse(input, &ParseOptions::default(), Some(&mut errors))
+        .expect("a recovering parse collects errors instead of returning them")
+        .into_owned();
+    (conf_str, errors)
 }
 
 /// Parse a config string.
@@ -278,13 +847,63 @@ fn parse_params(
 /// # Ok::<(), ParsingError>(())
 /// ```
 pub fn parse_conf_str(input: &str) -> Result<ConfStr, ParsingError> {
-    let mut iter = input.char_indices().peekable2();
-    let mut next_pos = 0;
-    let service = parse_ident(&mut iter, &mut next_pos)?;
-    let has_separator = parse_double_colon(&mut iter, &mut next_pos)?;
-    if !has_separator {
-        return Ok(ConfStr::new(service, Params::new()));
-    }
-    let params = parse_params(&mut iter, &mut next_pos, input.len())?;
-    Ok(ConfStr::new(service, params))
+    parse_conf_str_ref(input).map(ConfStrRef::into_owned)
+}
+
+/// Parse a config string with options, e.g. to allow repeated keys.
+///
+/// ```
+/// use questdb_confstr::{parse_conf_str_with, DuplicateKeys, ParseOptions};
+/// # use questdb_confstr::ParsingError;
+/// let input = "http::addr=a:9000;addr=b:9000;";
+/// let options = ParseOptions::new().duplicate_keys(DuplicateKeys::Collect);
+/// let config = parse_conf_str_with(input, &options)?;
+/// assert_eq!(config.get("addr"), Some("a:9000"));
+/// assert_eq!(config.get_all("addr").collect::<Vec<_>>(), ["a:9000", "b:9000"]);
+///
+/// let options = ParseOptions::new().duplicate_keys(DuplicateKeys::LastWins);
+/// let config = parse_conf_str_with(input, &options)?;
+/// assert_eq!(config.get_all("addr").collect::<Vec<_>>(), ["b:9000"]);
+/// # Ok::<(), ParsingError>(())
+/// ```
+pub fn parse_conf_str_with(input: &str, options: &ParseOptions) -> Result<ConfStr, ParsingError> {
+    parse_conf_str_ref_with(input, options).map(ConfStrRef::into_owned)
+}
+
+/// Parse a config string from raw bytes, e.g. as received over FFI.
+///
+/// Invalid UTF-8 is reported as [`ErrorKind::InvalidUtf8`], positioned at
+/// the first invalid byte.
+///
+/// ```
+/// use questdb_confstr::{parse_conf_str_bytes, ErrorKind};
+/// let config = parse_conf_str_bytes(b"http::addr=localhost:9000;").unwrap();
+/// assert_eq!(config.get("addr"), Some("localhost:9000"));
+///
+/// let err = parse_conf_str_bytes(b"http::user=a\xffb;").unwrap_err();
+/// assert_eq!(err.kind(), ErrorKind::InvalidUtf8);
+/// assert_eq!(err.position(), 12);
+/// ```
+pub fn parse_conf_str_bytes(input: &[u8]) -> Result<ConfStr, ParsingError> {
+    parse_conf_str_bytes_with(input, &ParseOptions::default())
+}
+
+/// Same as [`parse_conf_str_bytes`], with options.
+pub fn parse_conf_str_bytes_with(
+    input: &[u8],
+    options: &ParseOptions,
+) -> Result<ConfStr, ParsingError> {
+    parse_conf_str_with(from_utf8(input)?, options)
+}
+
+/// Parse a config string from an `OsStr`, such as the value of an
+/// environment variable read with [`std::env::var_os`].
+///
+/// Same as [`parse_conf_str_bytes`] on the string's encoded bytes, so on
+/// Windows positions are offsets into its WTF-8 encoding.
+///
+/// Requires the `std` feature.
+#[cfg(feature = "std")]
+pub fn parse_conf_str_os(input: &OsStr) -> Result<ConfStr, ParsingError> {
+    parse_conf_str_bytes(input.as_encoded_bytes())
 }
@@ -0,0 +1,114 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::{ParsingError, Position};
+
+/// A position in the input, in the units editors and other languages use.
+///
+/// Lines and columns are 1-based. Lines are separated by `\n`, so a `\r\n`
+/// line ending also works.
+///
+/// ```
+/// use questdb_confstr::Location;
+/// let input = "# profile\nhttp::user=Zoë😀;pass";
+/// let location = Location::new(input, input.find("pass").unwrap());
+/// assert_eq!(location.byte(), 30);
+/// assert_eq!(location.char(), 26);
+/// assert_eq!(location.line(), 2);
+/// assert_eq!(location.column(), 17);
+/// assert_eq!(location.utf16_column(), 18);
+/// ```
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Location {
+    byte: usize,
+    char: usize,
+    line: usize,
+    column: usize,
+    utf16_column: usize,
+}
+
+impl Location {
+    /// Locate the byte `position` in `input`.
+    ///
+    /// A position past the end of the input is clamped to its end,
+    /// and one within a char to the start of that char.
+    pub fn new(input: &str, position: Position) -> Self {
+        let mut byte = position.min(input.len());
+        while !input.is_char_boundary(byte) {
+            byte -= 1;
+        }
+        let mut location = Location {
+            byte,
+            char: 0,
+            line: 1,
+            column: 1,
+            utf16_column: 1,
+        };
+        for c in input[..byte].chars() {
+            location.char += 1;
+            if c == '\n' {
+                location.line += 1;
+                location.column = 1;
+                location.utf16_column = 1;
+            } else {
+                location.column += 1;
+                location.utf16_column += c.len_utf16();
+            }
+        }
+        location
+    }
+
+    /// Byte offset in the input.
+    pub fn byte(&self) -> usize {
+        self.byte
+    }
+
+    /// Offset in chars (Unicode scalar values) in the input.
+    pub fn char(&self) -> usize {
+        self.char
+    }
+
+    /// Line number, starting from 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column in chars within the line, starting from 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Column in UTF-16 code units within the line, starting from 1,
+    /// as used by JavaScript, Java and the Language Server Protocol.
+    pub fn utf16_column(&self) -> usize {
+        self.utf16_column
+    }
+}
+
+impl ParsingError {
+    /// Locate the error in the `input` it was parsed from.
+    pub fn location(&self, input: &str) -> Location {
+        Location::new(input, self.position())
+    }
+}
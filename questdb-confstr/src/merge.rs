@@ -0,0 +1,171 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::{ConfStr, Params};
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+
+/// Error merging configuration strings.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum MergeError {
+    /// There were no configuration strings to merge.
+    NoLayers,
+
+    /// A layer's service differs from the first layer's.
+    ServiceMismatch {
+        expected: String,
+        found: String,
+        source: Option<String>,
+    },
+}
+
+impl Display for MergeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            MergeError::NoLayers => write!(f, "no configuration strings to merge"),
+            MergeError::ServiceMismatch {
+                expected,
+                found,
+                source,
+            } => {
+                write!(
+                    f,
+                    "service mismatch, expected {:?}, found {:?}",
+                    expected, found
+                )?;
+                if let Some(source) = source {
+                    write!(f, " in {:?}", source)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MergeError {}
+
+impl ConfStr {
+    /// Tag every parameter as supplied by the named layer, e.g. `"defaults"`
+    /// or `"env"`, for [`ConfStr::source_of`] to report after a merge.
+    pub fn with_source(mut self, source: impl Into<String>) -> Self {
+        let source = source.into();
+        self.sources = vec![Some(source); self.params.len()];
+        self
+    }
+
+    /// Name of the layer that supplied the parameter, if it was tagged
+    /// with [`ConfStr::with_source`].
+    pub fn source_of(&self, key: &str) -> Option<&str> {
+        let index = self.params.index_of(key)?;
+        self.sources.get(index)?.as_deref()
+    }
+
+    /// Merge configuration strings, from lowest to highest precedence.
+    ///
+    /// A key set by several layers takes the value(s) of the last one, but keeps
+    /// the position where it first appeared. All layers must have the same
+    /// service name. Spans are dropped, since the params come from different inputs.
+    ///
+    /// ```
+    /// use questdb_confstr::{parse_conf_str, ConfStr};
+    /// let defaults = parse_conf_str("http::retry_timeout=10000;auto_flush=on;")
+    ///     .unwrap()
+    ///     .with_source("defaults");
+    /// let env = parse_conf_str("http::addr=db:9000;retry_timeout=500;")
+    ///     .unwrap()
+    ///     .with_source("QDB_CLIENT_CONF");
+    /// let config = ConfStr::merge([&defaults, &env]).unwrap();
+    /// assert_eq!(
+    ///     config.to_conf_string().unwrap(),
+    ///     "http::retry_timeout=500;auto_flush=on;addr=db:9000;");
+    /// assert_eq!(config.source_of("retry_timeout"), Some("QDB_CLIENT_CONF"));
+    /// assert_eq!(config.source_of("auto_flush"), Some("defaults"));
+    /// ```
+    pub fn merge<'a>(layers: impl IntoIterator<Item = &'a ConfStr>) -> Result<ConfStr, MergeError> {
+        let mut layers = layers.into_iter();
+        let first = layers.next().ok_or(MergeError::NoLayers)?;
+        let mut merged = ConfStr::new(first.service.clone(), first.params.clone());
+        merged.sources = first.sources.clone();
+        merged.sources.resize(merged.params.len(), None);
+        for layer in layers {
+            merged.merge_layer(layer)?;
+        }
+        Ok(merged)
+    }
+
+    /// Merge `other` on top of this configuration string.
+    ///
+    /// Same as `ConfStr::merge([self, other])`.
+    pub fn overlay(&self, other: &ConfStr) -> Result<ConfStr, MergeError> {
+        ConfStr::merge([self, other])
+    }
+
+    fn merge_layer(&mut self, layer: &ConfStr) -> Result<(), MergeError> {
+        if layer.service != self.service {
+            return Err(MergeError::ServiceMismatch {
+                expected: self.service.clone(),
+                found: layer.service.clone(),
+                source: layer.sources.iter().flatten().next().cloned(),
+            });
+        }
+        // A key's values in the layer replace all of its previous values, in place.
+        let mut params = Params::new();
+        let mut sources = Vec::new();
+        let mut append = |key: &str, value: &str, source: Option<&String>| {
+            params.append(key.to_string(), value.to_string());
+            sources.push(source.cloned());
+        };
+        for (index, (key, value)) in self.params.iter().enumerate() {
+            if !layer.params.contains_key(key) {
+                append(key, value, self.sources.get(index).and_then(Option::as_ref));
+            } else if self.params.index_of(key) == Some(index) {
+                for (index, (layer_key, value)) in layer.params.iter().enumerate() {
+                    if layer_key == key {
+                        append(
+                            key,
+                            value,
+                            layer.sources.get(index).and_then(Option::as_ref),
+                        );
+                    }
+                }
+            }
+        }
+        for (index, (key, value)) in layer.params.iter().enumerate() {
+            if !self.params.contains_key(key) {
+                append(
+                    key,
+                    value,
+                    layer.sources.get(index).and_then(Option::as_ref),
+                );
+            }
+        }
+        self.params = params;
+        self.sources = sources;
+        Ok(())
+    }
+}
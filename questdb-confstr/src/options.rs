@@ -0,0 +1,64 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+/// How the parser handles a key that appears more than once.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum DuplicateKeys {
+    /// Fail with [`ErrorKind::DuplicateKey`](crate::ErrorKind::DuplicateKey).
+    #[default]
+    Error,
+
+    /// Keep the last value, at the position where the key first appeared.
+    LastWins,
+
+    /// Keep every value, e.g. to list several addresses for failover.
+    ///
+    /// Use [`ConfStr::get_all`](crate::ConfStr::get_all) to read them.
+    Collect,
+}
+
+/// Options for [`parse_conf_str_with`](crate::parse_conf_str_with).
+///
+/// The defaults match [`parse_conf_str`](crate::parse_conf_str).
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ParseOptions {
+    duplicate_keys: DuplicateKeys,
+}
+
+impl ParseOptions {
+    /// The default options.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set how repeated keys are handled. Defaults to [`DuplicateKeys::Error`].
+    pub fn duplicate_keys(mut self, duplicate_keys: DuplicateKeys) -> Self {
+        self.duplicate_keys = duplicate_keys;
+        self
+    }
+
+    pub(crate) fn get_duplicate_keys(&self) -> DuplicateKeys {
+        self.duplicate_keys
+    }
+}
@@ -0,0 +1,204 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+#[cfg(feature = "zeroize")]
+use crate::secret::wipe;
+use crate::{Key, Value};
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Debug, Formatter};
+use core::slice;
+
+/// Parameters, in the order they appear in the configuration string.
+///
+/// The pairs are stored in a `Vec`, alongside an ordered index for fast lookup by key.
+#[derive(Clone, Default)]
+pub struct Params {
+    entries: Vec<(Key, Value)>,
+    index: BTreeMap<Key, usize>,
+}
+
+impl Params {
+    /// Create an empty set of parameters.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Number of parameters.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// True if there are no parameters.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Get a parameter's value.
+    ///
+    /// If the key has several values, this is the first one.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.index_of(key)
+            .map(|index| self.entries[index].1.as_str())
+    }
+
+    /// Iterate over every value of the key, in insertion order.
+    ///
+    /// Keys only have several values if added with [`Params::append`], e.g. when
+    /// parsing with [`DuplicateKeys::Collect`](crate::DuplicateKeys::Collect).
+    pub fn get_all<'a>(&'a self, key: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.iter()
+            .filter(move |(k, _)| *k == key)
+            .map(|(_, value)| value)
+    }
+
+    /// Index of the key's first value in insertion order.
+    pub(crate) fn index_of(&self, key: &str) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+
+    /// True if the parameter is present.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Insert a parameter.
+    ///
+    /// A new key is appended at the end.
+    /// If the key is already present, its (first) value is replaced in place
+    /// and the old value is returned.
+    pub fn insert(&mut self, key: Key, value: Value) -> Option<Value> {
+        match self.index.get(&key) {
+            Some(&index) => Some(core::mem::replace(&mut self.entries[index].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Replace the value of the entry at `index`, returning the old value.
+    pub(crate) fn replace_at(&mut self, index: usize, value: Value) -> Value {
+        core::mem::replace(&mut self.entries[index].1, value)
+    }
+
+    /// Append a parameter, keeping any existing values for the key.
+    pub fn append(&mut self, key: Key, value: Value) {
+        if !self.index.contains_key(&key) {
+            self.index.insert(key.clone(), self.entries.len());
+        }
+        self.entries.push((key, value));
+    }
+
+    /// Iterate over the `(key, value)` pairs in insertion order.
+    pub fn iter(&self) -> ParamsIter<'_> {
+        ParamsIter {
+            inner: self.entries.iter(),
+        }
+    }
+
+    /// Iterate over the keys in insertion order.
+    pub fn keys(&self) -> impl Iterator<Item = &str> {
+        self.iter().map(|(key, _)| key)
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Params {
+    fn drop(&mut self) {
+        for (key, value) in self.entries.iter_mut() {
+            wipe(key);
+            wipe(value);
+        }
+        for mut key in core::mem::take(&mut self.index).into_keys() {
+            wipe(&mut key);
+        }
+    }
+}
+
+impl PartialEq for Params {
+    fn eq(&self, other: &Self) -> bool {
+        // The index is derived from the entries.
+        self.entries == other.entries
+    }
+}
+
+impl Eq for Params {}
+
+impl Debug for Params {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<(Key, Value)> for Params {
+    fn from_iter<T: IntoIterator<Item = (Key, Value)>>(iter: T) -> Self {
+        let mut params = Params::new();
+        for (key, value) in iter {
+            params.insert(key, value);
+        }
+        params
+    }
+}
+
+impl<'a> IntoIterator for &'a Params {
+    type Item = (&'a str, &'a str);
+    type IntoIter = ParamsIter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+/// Iterator over the parameters, in insertion order.
+#[derive(Clone, Debug)]
+pub struct ParamsIter<'a> {
+    inner: slice::Iter<'a, (Key, Value)>,
+}
+
+impl<'a> Iterator for ParamsIter<'a> {
+    type Item = (&'a str, &'a str);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.inner.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ParamsIter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.inner
+            .next_back()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+}
+
+impl ExactSizeIterator for ParamsIter<'_> {}
@@ -1,5 +1,5 @@
-use std::fmt::Debug;
-use std::iter::Fuse;
+use core::fmt::Debug;
+use core::iter::Fuse;
 
 /// An iterator that allows peeking at the next two items.
 #[derive(Clone, Debug)]
@@ -65,6 +65,7 @@ impl<I: Iterator> Peekable2Ext for I {}
 #[cfg(test)]
 mod tests {
     use super::*;
+    use alloc::vec;
 
     #[test]
     fn test_peekable2() {
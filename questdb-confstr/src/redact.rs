@@ -0,0 +1,166 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::{is_invalid_value_char, ConfStr};
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Display, Formatter, Write};
+
+/// Keys whose values are masked by [`ConfStr::redacted`] unless configured otherwise.
+pub const DEFAULT_SENSITIVE_KEYS: &[&str] = &[
+    "password",
+    "token",
+    "token_x",
+    "token_y",
+    "tls_roots_password",
+    "account_key",
+];
+
+const MASK: &str = "****";
+
+/// How the values of sensitive keys are masked.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Mask {
+    /// Replace the whole value with `****`.
+    #[default]
+    Full,
+
+    /// Replace the value with `****` followed by its last `n` characters.
+    ///
+    /// To avoid revealing most of a short secret, the value is fully masked
+    /// unless it has at least `2 * n` characters.
+    RevealLast(usize),
+}
+
+/// Formats a [`ConfStr`] as a configuration string, masking the values of
+/// sensitive keys.
+///
+/// Returned by [`ConfStr::redacted`].
+///
+/// ```
+/// use questdb_confstr::{parse_conf_str, Mask};
+/// let config = parse_conf_str(
+///     "http::addr=localhost:9000;username=admin;password=quest;api_key=0123456789;").unwrap();
+/// assert_eq!(
+///     config.redacted().to_string(),
+///     "http::addr=localhost:9000;username=admin;password=****;api_key=0123456789;");
+/// assert_eq!(
+///     config.redacted().sensitive_key("api_key").mask(Mask::RevealLast(2)).to_string(),
+///     "http::addr=localhost:9000;username=admin;password=****st;api_key=****89;");
+/// ```
+#[derive(Clone)]
+pub struct Redacted<'a> {
+    conf_str: &'a ConfStr,
+    sensitive_keys: Vec<Cow<'a, str>>,
+    mask: Mask,
+}
+
+impl<'a> Redacted<'a> {
+    /// Also mask the values of `key`.
+    pub fn sensitive_key(mut self, key: impl Into<Cow<'a, str>>) -> Self {
+        self.sensitive_keys.push(key.into());
+        self
+    }
+
+    /// Set how sensitive values are masked. Defaults to [`Mask::Full`].
+    pub fn mask(mut self, mask: Mask) -> Self {
+        self.mask = mask;
+        self
+    }
+
+    fn is_sensitive(&self, key: &str) -> bool {
+        self.sensitive_keys.iter().any(|sensitive| sensitive == key)
+    }
+}
+
+fn write_value(f: &mut Formatter<'_>, value: &str) -> fmt::Result {
+    for c in value.chars() {
+        match c {
+            ';' => f.write_str(";;")?,
+            // Only reachable for a `ConfStr` built with `ConfStr::new`: Keep the log line intact.
+            c if is_invalid_value_char(c) => write!(f, "{}", c.escape_default())?,
+            c => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
+
+impl Display for Redacted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}::", self.conf_str.service())?;
+        for (key, value) in self.conf_str.params() {
+            write!(f, "{}=", key)?;
+            if !self.is_sensitive(key) {
+                write_value(f, value)?;
+            } else {
+                f.write_str(MASK)?;
+                if let Mask::RevealLast(n) = self.mask {
+                    let len = value.chars().count();
+                    if n > 0 && len >= 2 * n {
+                        let start = value
+                            .char_indices()
+                            .nth(len - n)
+                            .map_or(value.len(), |(p, _)| p);
+                        write_value(f, &value[start..])?;
+                    }
+                }
+            }
+            f.write_char(';')?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Redacted<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(self, f)
+    }
+}
+
+impl ConfStr {
+    /// Format as a configuration string with the values of
+    /// [`DEFAULT_SENSITIVE_KEYS`] masked, e.g. for logging.
+    ///
+    /// Use [`Redacted::sensitive_key`] to mask more keys.
+    /// The output is meant for humans: Use [`ConfStr::to_conf_string`] to
+    /// serialize the actual values.
+    pub fn redacted(&self) -> Redacted<'_> {
+        Redacted {
+            conf_str: self,
+            sensitive_keys: DEFAULT_SENSITIVE_KEYS
+                .iter()
+                .map(|key| Cow::Borrowed(*key))
+                .collect(),
+            mask: Mask::Full,
+        }
+    }
+}
+
+/// Same as [`ConfStr::redacted`] with the default sensitive keys.
+impl Display for ConfStr {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.redacted(), f)
+    }
+}
@@ -0,0 +1,262 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::secret::{push_str, wipe};
+use crate::{is_ident_char, parse_conf_str, Position, DEFAULT_SENSITIVE_KEYS};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+
+/// A change made by [`repair`].
+///
+/// Positions are byte positions in the original input.
+/// Values are never included.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Fix {
+    /// Removed quotes around the whole string, e.g. left over from a shell.
+    RemovedQuotes { position: Position },
+
+    /// Replaced a single `:` after the service name with `::`.
+    DoubledColon { position: Position },
+
+    /// Removed whitespace around the input, the service name, a key, a value,
+    /// `=` or `;`.
+    RemovedWhitespace { position: Position },
+
+    /// Escaped a `;` in the value of a sensitive key as `;;`, because the
+    /// text following it is not a parameter.
+    EscapedSemicolon { key: String, position: Position },
+}
+
+impl Fix {
+    /// Byte position of the change in the original input.
+    pub fn position(&self) -> Position {
+        match self {
+            Fix::RemovedQuotes { position } => *position,
+            Fix::DoubledColon { position } => *position,
+            Fix::RemovedWhitespace { position } => *position,
+            Fix::EscapedSemicolon { position, .. } => *position,
+        }
+    }
+}
+
+impl Display for Fix {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Fix::RemovedQuotes { .. } => write!(f, "removed surrounding quotes")?,
+            Fix::DoubledColon { .. } => write!(f, "replaced `:` with `::` after the service name")?,
+            Fix::RemovedWhitespace { .. } => write!(f, "removed whitespace")?,
+            Fix::EscapedSemicolon { key, .. } => {
+                write!(f, "escaped `;` as `;;` in value for key {:?}", key)?
+            }
+        }
+        write!(f, " at position {}", self.position())
+    }
+}
+
+/// Rewrite common mistakes in a configuration string into a valid one,
+/// listing each change made.
+///
+/// The mistakes repaired are:
+/// * Quotes around the whole string, e.g. from copy-pasting a shell command.
+/// * A single `:` between the service name and the parameters.
+/// * Whitespace around the service name, keys, values, `=` and `;`.
+/// * An unescaped `;` in the value of a sensitive key, such as `password`,
+///   unless the text following it is the next `key=`.
+///
+/// Returns the input unchanged with no fixes if it is already valid, and
+/// `None` if it is still invalid after these repairs.
+///
+/// ```
+/// use questdb_confstr::{repair, Fix};
+/// let (repaired, fixes) = repair("'http:addr=localhost:9000; password=a;b'").unwrap();
+/// assert_eq!(repaired, "http::addr=localhost:9000;password=a;;b");
+/// assert_eq!(fixes.len(), 4);
+/// assert_eq!(fixes[1], Fix::DoubledColon { position: 5 });
+/// assert_eq!(fixes[3].to_string(),
+///     "escaped `;` as `;;` in value for key \"password\" at position 37");
+///
+/// assert_eq!(repair("http::addr=a;b;"), None);
+/// ```
+pub fn repair(input: &str) -> Option<(String, Vec<Fix>)> {
+    // Whitespace in values is valid: Leave valid input alone.
+    if parse_conf_str(input).is_ok() {
+        return Some((input.to_string(), Vec::new()));
+    }
+    let mut fixes = Vec::new();
+    let mut trailing = Vec::new();
+    let (mut pos, end) = trim(input, &mut fixes, &mut trailing);
+    // Grown with `push_str`, which wipes the old buffer, as escapes are added.
+    let mut out = String::with_capacity(input.len() + 1);
+
+    let service_end = ident_end(input, pos, end);
+    push_str(&mut out, &input[pos..service_end]);
+    pos = skip_whitespace(input, service_end, end, &mut fixes);
+    let separator = match &input[pos..end] {
+        rest if rest.starts_with("::") => 2,
+        rest if rest.starts_with(':') && !rest.starts_with(":/") => {
+            fixes.push(Fix::DoubledColon { position: pos });
+            1
+        }
+        _ => 0,
+    };
+    if separator > 0 {
+        push_str(&mut out, "::");
+        pos += separator;
+    } else {
+        // Nothing we know how to repair: Let the parser decide.
+        push_str(&mut out, &input[pos..end]);
+        pos = end;
+    }
+
+    while pos < end {
+        pos = skip_whitespace(input, pos, end, &mut fixes);
+        let key_end = ident_end(input, pos, end);
+        let key = &input[pos..key_end];
+        push_str(&mut out, key);
+        pos = skip_whitespace(input, key_end, end, &mut fixes);
+        if !input[pos..end].starts_with('=') {
+            push_str(&mut out, &input[pos..end]);
+            break;
+        }
+        push_str(&mut out, "=");
+        pos = skip_whitespace(input, pos + 1, end, &mut fixes);
+        pos = repair_value(input, key, pos, end, &mut out, &mut fixes);
+        if pos < end {
+            // At the `;` ending the value.
+            push_str(&mut out, ";");
+            pos += 1;
+        }
+    }
+    fixes.append(&mut trailing);
+
+    match parse_conf_str(&out) {
+        Ok(_) => Some((out, fixes)),
+        Err(_) => {
+            wipe(&mut out);
+            None
+        }
+    }
+}
+
+/// Strip whitespace and quotes around the whole input, returning the range left.
+fn trim(input: &str, fixes: &mut Vec<Fix>, trailing: &mut Vec<Fix>) -> (Position, Position) {
+    let mut start = 0;
+    let mut end = input.len();
+    for round in 0..2 {
+        let trimmed = input[start..end].trim_start();
+        if trimmed.len() < end - start {
+            fixes.push(Fix::RemovedWhitespace { position: start });
+            start = end - trimmed.len();
+        }
+        let trimmed = input[start..end].trim_end();
+        if trimmed.len() < end - start {
+            end = start + trimmed.len();
+            trailing.insert(0, Fix::RemovedWhitespace { position: end });
+        }
+        let bytes = &input.as_bytes()[start..end];
+        let quoted = bytes.len() >= 2
+            && matches!(bytes[0], b'"' | b'\'')
+            && bytes[0] == bytes[bytes.len() - 1];
+        if round > 0 || !quoted {
+            break;
+        }
+        fixes.push(Fix::RemovedQuotes { position: start });
+        start += 1;
+        end -= 1;
+    }
+    (start, end)
+}
+
+/// Position after the identifier starting at `pos`.
+fn ident_end(input: &str, pos: Position, end: Position) -> Position {
+    input[pos..end]
+        .find(|c| !is_ident_char(c))
+        .map_or(end, |len| pos + len)
+}
+
+fn skip_whitespace(input: &str, pos: Position, end: Position, fixes: &mut Vec<Fix>) -> Position {
+    let trimmed = input[pos..end].trim_start();
+    if trimmed.len() == end - pos {
+        return pos;
+    }
+    fixes.push(Fix::RemovedWhitespace { position: pos });
+    end - trimmed.len()
+}
+
+/// True if `rest`, following a `;`, starts with the next `key=` or is empty.
+fn starts_param(rest: &str) -> bool {
+    let rest = rest.trim_start();
+    let key_len = rest.find(|c| !is_ident_char(c)).unwrap_or(rest.len());
+    rest.is_empty() || (key_len > 0 && rest[key_len..].trim_start().starts_with('='))
+}
+
+/// Copy the value starting at `pos` into `out`, returning the position of the
+/// `;` that ends it, or `end`.
+fn repair_value(
+    input: &str,
+    key: &str,
+    mut pos: Position,
+    end: Position,
+    out: &mut String,
+    fixes: &mut Vec<Fix>,
+) -> Position {
+    let sensitive = DEFAULT_SENSITIVE_KEYS.contains(&key);
+    let start = pos;
+    let mut escapes = Vec::new();
+    while pos < end {
+        let rest = &input[pos..end];
+        if rest.starts_with(";;") {
+            pos += 2;
+        } else if let Some(after) = rest.strip_prefix(';') {
+            if !sensitive || starts_param(after) {
+                break;
+            }
+            escapes.push(pos);
+            pos += 1;
+        } else {
+            pos += rest.chars().next().map_or(1, char::len_utf8);
+        }
+    }
+
+    let value_end = start + input[start..pos].trim_end().len();
+    let mut copied = start;
+    for escape in escapes {
+        push_str(out, &input[copied..escape]);
+        push_str(out, ";;");
+        copied = escape + 1;
+        fixes.push(Fix::EscapedSemicolon {
+            key: key.to_string(),
+            position: escape,
+        });
+    }
+    push_str(out, &input[copied..value_end]);
+    if value_end < pos {
+        fixes.push(Fix::RemovedWhitespace {
+            position: value_end,
+        });
+    }
+    pos
+}
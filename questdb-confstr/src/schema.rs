@@ -0,0 +1,255 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::field::__private::finish;
+use crate::typed::{
+    expected_type, parse_bool, parse_bytes, parse_duration_ms, EXPECTED_BOOL, EXPECTED_BYTES,
+    EXPECTED_DURATION_MS,
+};
+use crate::{ConfStr, FieldError, FromConfStrError, ValueError};
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+/// The type a parameter's value must parse as.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum ValueType {
+    /// Any value.
+    String,
+
+    /// `on`/`off` (or `true`/`false`), as for [`ConfStr::get_bool`].
+    Bool,
+
+    /// A signed 64-bit integer.
+    Integer,
+
+    /// An integer number of milliseconds, as for [`ConfStr::get_duration_ms`].
+    DurationMs,
+
+    /// A size in bytes with an optional suffix, as for [`ConfStr::get_bytes`].
+    Bytes,
+}
+
+impl ValueType {
+    /// Description of the expected format, if `value` doesn't match the type.
+    fn check(self, value: &str) -> Option<Cow<'static, str>> {
+        match self {
+            ValueType::String => None,
+            ValueType::Bool => parse_bool(value).is_none().then(|| EXPECTED_BOOL.into()),
+            ValueType::Integer => value
+                .parse::<i64>()
+                .is_err()
+                .then(|| expected_type::<i64>().into()),
+            ValueType::DurationMs => parse_duration_ms(value)
+                .is_none()
+                .then(|| EXPECTED_DURATION_MS.into()),
+            ValueType::Bytes => parse_bytes(value).is_none().then(|| EXPECTED_BYTES.into()),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+struct KeySchema {
+    key: String,
+    value_type: ValueType,
+    required: bool,
+    default: Option<String>,
+}
+
+/// The keys accepted by a single service.
+#[derive(Debug, Clone)]
+pub struct ServiceSchema {
+    service: String,
+    keys: Vec<KeySchema>,
+}
+
+impl ServiceSchema {
+    /// Start declaring the keys of a service.
+    pub fn new(service: impl Into<String>) -> Self {
+        ServiceSchema {
+            service: service.into(),
+            keys: Vec::new(),
+        }
+    }
+
+    fn key(mut self, key: KeySchema) -> Self {
+        // Redeclaring a key replaces it.
+        self.keys.retain(|existing| existing.key != key.key);
+        self.keys.push(key);
+        self
+    }
+
+    /// Declare a key that must be present.
+    pub fn required(self, key: impl Into<String>, value_type: ValueType) -> Self {
+        self.key(KeySchema {
+            key: key.into(),
+            value_type,
+            required: true,
+            default: None,
+        })
+    }
+
+    /// Declare a key that may be omitted.
+    pub fn optional(self, key: impl Into<String>, value_type: ValueType) -> Self {
+        self.key(KeySchema {
+            key: key.into(),
+            value_type,
+            required: false,
+            default: None,
+        })
+    }
+
+    /// Declare a key that may be omitted, in which case
+    /// [`Schema::apply_defaults`] sets it to `default`.
+    pub fn with_default(
+        self,
+        key: impl Into<String>,
+        value_type: ValueType,
+        default: impl Into<String>,
+    ) -> Self {
+        self.key(KeySchema {
+            key: key.into(),
+            value_type,
+            required: false,
+            default: Some(default.into()),
+        })
+    }
+
+    /// The service name.
+    pub fn service(&self) -> &str {
+        &self.service
+    }
+
+    fn get(&self, key: &str) -> Option<&KeySchema> {
+        self.keys.iter().find(|schema| schema.key == key)
+    }
+}
+
+/// Declares, for each service, the keys it accepts.
+///
+/// ```
+/// use questdb_confstr::{parse_conf_str, Schema, ServiceSchema, ValueType};
+///
+/// let schema = Schema::new().service(
+///     ServiceSchema::new("http")
+///         .required("addr", ValueType::String)
+///         .with_default("retry_timeout", ValueType::DurationMs, "10000"));
+///
+/// let config = parse_conf_str("http::addr=localhost:9000;retry_timout=500;").unwrap();
+/// let err = schema.validate(&config).unwrap_err();
+/// assert_eq!(
+///     err.to_string(),
+///     "key \"retry_timout\" is not supported for service \"http\" at position 26");
+///
+/// let config = parse_conf_str("http::addr=localhost:9000;").unwrap();
+/// assert!(schema.validate(&config).is_ok());
+/// assert_eq!(schema.apply_defaults(&config).get("retry_timeout"), Some("10000"));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Schema {
+    services: Vec<ServiceSchema>,
+}
+
+impl Schema {
+    /// An empty schema, which accepts no services.
+    pub fn new() -> Self {
+        Schema::default()
+    }
+
+    /// Add a service. Adding a service twice replaces it.
+    pub fn service(mut self, service: ServiceSchema) -> Self {
+        self.services
+            .retain(|existing| existing.service != service.service);
+        self.services.push(service);
+        self
+    }
+
+    /// The schema for a service, if declared.
+    pub fn get(&self, service: &str) -> Option<&ServiceSchema> {
+        self.services
+            .iter()
+            .find(|schema| schema.service == service)
+    }
+
+    /// Check the configuration string against the schema.
+    ///
+    /// All unsupported services, unknown keys, missing required keys and
+    /// type mismatches are reported together.
+    pub fn validate(&self, conf_str: &ConfStr) -> Result<(), FromConfStrError> {
+        let mut errors = Vec::new();
+        let Some(service) = self.get(conf_str.service()) else {
+            errors.push(FieldError::UnsupportedService {
+                service: conf_str.service().to_string(),
+                allowed: self
+                    .services
+                    .iter()
+                    .map(|schema| schema.service.clone())
+                    .collect(),
+                position: conf_str.service_span().map(|span| span.start),
+            });
+            return finish(errors);
+        };
+        for (index, (key, value)) in conf_str.params().iter().enumerate() {
+            let Some(schema) = service.get(key) else {
+                errors.push(FieldError::UnsupportedKey {
+                    key: key.to_string(),
+                    service: service.service.clone(),
+                    position: conf_str.key_span_at(index).map(|span| span.start),
+                });
+                continue;
+            };
+            if let Some(expected) = schema.value_type.check(value) {
+                errors.push(FieldError::InvalidValue(ValueError::new(
+                    key,
+                    expected,
+                    conf_str.value_span_at(index).map(|span| span.start),
+                )));
+            }
+        }
+        for schema in &service.keys {
+            if schema.required && conf_str.get(&schema.key).is_none() {
+                errors.push(FieldError::MissingKey(schema.key.clone()));
+            }
+        }
+        finish(errors)
+    }
+
+    /// Return a copy of the configuration string with the declared defaults
+    /// added for absent keys.
+    ///
+    /// Defaults are appended after the existing params and have no spans.
+    pub fn apply_defaults(&self, conf_str: &ConfStr) -> ConfStr {
+        let mut conf_str = conf_str.clone();
+        if let Some(service) = self.get(conf_str.service()) {
+            for schema in &service.keys {
+                if let Some(default) = &schema.default {
+                    if !conf_str.params.contains_key(&schema.key) {
+                        conf_str.params.insert(schema.key.clone(), default.clone());
+                    }
+                }
+            }
+        }
+        conf_str
+    }
+}
@@ -0,0 +1,100 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::ConfStr;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Debug, Formatter};
+
+/// A sensitive value, such as a password.
+///
+/// It can't be printed by accident: It does not implement `Display`,
+/// its `Debug` output is masked and it is not `Clone`.
+/// Call [`Secret::expose_secret`] to access the value.
+pub struct Secret<T>(T);
+
+impl<'a> Secret<&'a str> {
+    /// Access the value.
+    pub fn expose_secret(&self) -> &'a str {
+        self.0
+    }
+}
+
+impl<T> Debug for Secret<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(****)")
+    }
+}
+
+impl ConfStr {
+    /// Get the value of a sensitive key, such as `password`, as a [`Secret`].
+    ///
+    /// Returns `None` only if the key is not set. The caller decides which keys
+    /// are sensitive, e.g. those in
+    /// [`DEFAULT_SENSITIVE_KEYS`](crate::DEFAULT_SENSITIVE_KEYS) or an `api_key`.
+    ///
+    /// [`ConfStr::get`] still returns a plain `&str` for every key, including
+    /// sensitive ones, so that existing callers keep working: Prefer this method
+    /// wherever the value may end up in logs.
+    ///
+    /// ```
+    /// use questdb_confstr::parse_conf_str;
+    /// let config = parse_conf_str("http::username=admin;password=quest;").unwrap();
+    /// let password = config.get_secret("password").unwrap();
+    /// assert_eq!(format!("{:?}", password), "Secret(****)");
+    /// assert_eq!(password.expose_secret(), "quest");
+    /// assert!(config.get_secret("token").is_none());
+    /// ```
+    pub fn get_secret(&self, key: &str) -> Option<Secret<&str>> {
+        self.get(key).map(Secret)
+    }
+}
+
+/// Wipe a buffer that may hold a secret, if the `zeroize` feature is enabled.
+pub(crate) fn wipe(buf: &mut String) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(buf);
+    #[cfg(not(feature = "zeroize"))]
+    let _ = buf;
+}
+
+/// Same as [`wipe`], for bytes.
+pub(crate) fn wipe_bytes(buf: &mut Vec<u8>) {
+    #[cfg(feature = "zeroize")]
+    zeroize::Zeroize::zeroize(buf);
+    #[cfg(not(feature = "zeroize"))]
+    let _ = buf;
+}
+
+/// Append to `buf`, wiping the old allocation if `buf` has to grow.
+pub(crate) fn push_str(buf: &mut String, s: &str) {
+    if buf.capacity() - buf.len() < s.len() {
+        let mut grown = String::with_capacity((buf.len() + s.len()).max(buf.capacity() * 2));
+        grown.push_str(buf);
+        wipe(buf);
+        *buf = grown;
+    }
+    buf.push_str(s);
+}
@@ -0,0 +1,602 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+//! Serialize a user struct into a configuration string with serde.
+//!
+//! Requires the `serde` feature.
+//!
+//! The struct (or map) is flattened into `key=value;` pairs:
+//! * Numbers and strings are written as-is, booleans as `on`/`off`.
+//! * `None` fields are skipped.
+//! * Unit enum variants are written as their name.
+//! * Nested structs, maps and sequences are rejected.
+//!
+//! ```
+//! use serde::Serialize;
+//! # use questdb_confstr::ser::Error;
+//!
+//! #[derive(Serialize)]
+//! struct Config {
+//!     addr: String,
+//!     password: Option<String>,
+//!     retry_timeout: Option<u64>,
+//!     tls_verify: bool,
+//! }
+//!
+//! let config = Config {
+//!     addr: "localhost:9000".to_string(),
+//!     password: Some("a;b".to_string()),
+//!     retry_timeout: None,
+//!     tls_verify: true,
+//! };
+//! let conf_str = questdb_confstr::to_string("https", &config)?;
+//! assert_eq!(conf_str, "https::addr=localhost:9000;password=a;;b;tls_verify=on;");
+//! # Ok::<(), Error>(())
+//! ```
+
+use crate::de::SERVICE_FIELD;
+use crate::{ConfStrBuilder, ParsingError, Position};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+use serde::ser::{self, Impossible, Serialize};
+
+/// Error serializing into a configuration string.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Error {
+    msg: String,
+    key: Option<String>,
+    position: Option<Position>,
+}
+
+impl Error {
+    /// The key whose value could not be serialized, if any.
+    pub fn key(&self) -> Option<&str> {
+        self.key.as_deref()
+    }
+
+    /// Byte position in the output where the configuration string would be invalid, if known.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+
+    fn value(key: &str, msg: impl Display) -> Self {
+        Error {
+            msg: msg.to_string(),
+            key: Some(key.to_string()),
+            position: None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "cannot serialize key {:?}, {}", key, self.msg)?,
+            None => write!(f, "{}", self.msg)?,
+        }
+        if let Some(position) = self.position {
+            write!(f, " at position {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+impl serde::ser::StdError for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Self {
+        Error {
+            msg: msg.to_string(),
+            key: None,
+            position: None,
+        }
+    }
+}
+
+impl From<ParsingError> for Error {
+    fn from(err: ParsingError) -> Self {
+        Error {
+            msg: err.kind().to_string(),
+            key: None,
+            position: Some(err.position()),
+        }
+    }
+}
+
+/// Serialize a struct or map of scalars into a configuration string for `service`.
+///
+/// A field renamed to [`SERVICE_FIELD`] is skipped: The `service` argument is used instead.
+///
+/// The output is validated with the same rules as the parser, so it always parses back.
+pub fn to_string<T: Serialize + ?Sized>(service: &str, value: &T) -> Result<String, Error> {
+    let pairs = value.serialize(Serializer)?;
+    let conf_str = pairs
+        .into_iter()
+        .fold(ConfStrBuilder::new(service), |builder, (key, value)| {
+            builder.param(key, value)
+        })
+        .build()?;
+    Ok(conf_str.to_conf_string()?)
+}
+
+type Pairs = Vec<(String, String)>;
+
+fn top_level_error() -> Error {
+    ser::Error::custom("expected a struct or map at the top level")
+}
+
+/// Top-level serializer: Only accepts structs and maps.
+struct Serializer;
+
+macro_rules! reject {
+    ($err:expr; $($method:ident($($arg:ty),*);)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<Self::Ok, Error> {
+                Err($err)
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for Serializer {
+    type Ok = Pairs;
+    type Error = Error;
+    type SerializeSeq = Impossible<Pairs, Error>;
+    type SerializeTuple = Impossible<Pairs, Error>;
+    type SerializeTupleStruct = Impossible<Pairs, Error>;
+    type SerializeTupleVariant = Impossible<Pairs, Error>;
+    type SerializeMap = PairsSerializer;
+    type SerializeStruct = PairsSerializer;
+    type SerializeStructVariant = Impossible<Pairs, Error>;
+
+    reject! {
+        top_level_error();
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Pairs, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Pairs, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Pairs, Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(top_level_error())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Ok(PairsSerializer::default())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Ok(PairsSerializer::default())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(top_level_error())
+    }
+}
+
+/// Collects the fields of the top-level struct or map.
+#[derive(Default)]
+struct PairsSerializer {
+    pairs: Pairs,
+    next_key: Option<String>,
+}
+
+impl PairsSerializer {
+    fn push<T: Serialize + ?Sized>(&mut self, key: String, value: &T) -> Result<(), Error> {
+        if let Some(value) = value.serialize(ValueSerializer { key: &key })? {
+            self.pairs.push((key, value));
+        }
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for PairsSerializer {
+    type Ok = Pairs;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        if key == SERVICE_FIELD {
+            return Ok(());
+        }
+        self.push(key.to_string(), value)
+    }
+
+    fn end(self) -> Result<Pairs, Error> {
+        Ok(self.pairs)
+    }
+}
+
+impl ser::SerializeMap for PairsSerializer {
+    type Ok = Pairs;
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.next_key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .next_key
+            .take()
+            .expect("serialize_value called before serialize_key");
+        self.push(key, value)
+    }
+
+    fn end(self) -> Result<Pairs, Error> {
+        Ok(self.pairs)
+    }
+}
+
+fn key_error() -> Error {
+    ser::Error::custom("map keys must be strings")
+}
+
+/// Serializes map keys, which must be strings.
+struct KeySerializer;
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_str(self, value: &str) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_char(self, value: char) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Error> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    reject! {
+        key_error();
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(key_error())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(key_error())
+    }
+}
+
+/// Serializes a scalar value, or `None` if the field should be skipped.
+struct ValueSerializer<'k> {
+    key: &'k str,
+}
+
+impl ValueSerializer<'_> {
+    fn nested(&self, what: &str) -> Error {
+        Error::value(self.key, format_args!("nested {} are not supported", what))
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method(self, value: $ty) -> Result<Option<String>, Error> {
+                Ok(Some(value.to_string()))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for ValueSerializer<'_> {
+    type Ok = Option<String>;
+    type Error = Error;
+    type SerializeSeq = Impossible<Option<String>, Error>;
+    type SerializeTuple = Impossible<Option<String>, Error>;
+    type SerializeTupleStruct = Impossible<Option<String>, Error>;
+    type SerializeTupleVariant = Impossible<Option<String>, Error>;
+    type SerializeMap = Impossible<Option<String>, Error>;
+    type SerializeStruct = Impossible<Option<String>, Error>;
+    type SerializeStructVariant = Impossible<Option<String>, Error>;
+
+    fn serialize_bool(self, value: bool) -> Result<Option<String>, Error> {
+        Ok(Some(if value { "on" } else { "off" }.to_string()))
+    }
+
+    serialize_display! {
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Option<String>, Error> {
+        Err(self.nested("byte arrays"))
+    }
+
+    fn serialize_none(self) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Option<String>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Option<String>, Error> {
+        Ok(None)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<Option<String>, Error> {
+        Ok(Some(variant.to_string()))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Option<String>, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Option<String>, Error> {
+        Err(self.nested("enum variants"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(self.nested("sequences"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(self.nested("sequences"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(self.nested("sequences"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(self.nested("enum variants"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(self.nested("maps"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, Error> {
+        Err(self.nested("structs"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(self.nested("enum variants"))
+    }
+}
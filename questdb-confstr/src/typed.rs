@@ -0,0 +1,187 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::{ConfStr, Position};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use core::time::Duration;
+
+pub(crate) const EXPECTED_BOOL: &str = "`on`, `off`, `true` or `false`";
+pub(crate) const EXPECTED_DURATION_MS: &str = "a duration in milliseconds";
+pub(crate) const EXPECTED_BYTES: &str = "a size in bytes, optionally suffixed by `k`, `m` or `g`";
+
+/// A parameter's value could not be converted to the requested type.
+///
+/// The error names the key and the expected format, but never the value itself.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ValueError {
+    key: String,
+    expected: Cow<'static, str>,
+    position: Option<Position>,
+}
+
+impl ValueError {
+    pub(crate) fn new(
+        key: impl Into<String>,
+        expected: impl Into<Cow<'static, str>>,
+        position: Option<Position>,
+    ) -> Self {
+        ValueError {
+            key: key.into(),
+            expected: expected.into(),
+            position,
+        }
+    }
+
+    /// The key whose value is invalid.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Description of the expected format.
+    pub fn expected(&self) -> &str {
+        &self.expected
+    }
+
+    /// Byte position of the value in the input string, if the `ConfStr` was parsed.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+}
+
+impl Display for ValueError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // Ensure no values are leaked in error messages.
+        write!(
+            f,
+            "invalid value for key {:?}, expected {}",
+            self.key, self.expected
+        )?;
+        if let Some(position) = self.position {
+            write!(f, " at position {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ValueError {}
+
+/// Short description of the expected type, e.g. "a value of type `u16`".
+pub(crate) fn expected_type<T>() -> String {
+    let name = core::any::type_name::<T>();
+    // Strip the module path from simple names, e.g. `core::net::IpAddr`.
+    let name = match name.contains('<') {
+        true => name,
+        false => name.rsplit("::").next().unwrap_or(name),
+    };
+    format!("a value of type `{}`", name)
+}
+
+pub(crate) fn parse_bool(value: &str) -> Option<bool> {
+    match value {
+        "on" | "true" => Some(true),
+        "off" | "false" => Some(false),
+        _ => None,
+    }
+}
+
+pub(crate) fn parse_duration_ms(value: &str) -> Option<Duration> {
+    value.parse::<u64>().ok().map(Duration::from_millis)
+}
+
+pub(crate) fn parse_bytes(value: &str) -> Option<u64> {
+    let (digits, multiplier) = match value.as_bytes().last()? {
+        b'k' | b'K' => (&value[..value.len() - 1], 1 << 10),
+        b'm' | b'M' => (&value[..value.len() - 1], 1 << 20),
+        b'g' | b'G' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    digits.parse::<u64>().ok()?.checked_mul(multiplier)
+}
+
+impl ConfStr {
+    fn get_with<T>(
+        &self,
+        key: &str,
+        expected: impl FnOnce() -> Cow<'static, str>,
+        parse: impl FnOnce(&str) -> Option<T>,
+    ) -> Result<Option<T>, ValueError> {
+        let Some(value) = self.get(key) else {
+            return Ok(None);
+        };
+        match parse(value) {
+            Some(parsed) => Ok(Some(parsed)),
+            None => Err(ValueError::new(
+                key,
+                expected(),
+                self.value_span(key).map(|span| span.start),
+            )),
+        }
+    }
+
+    /// Get a parameter parsed with [`FromStr`].
+    ///
+    /// Returns `Ok(None)` if the key is absent.
+    ///
+    /// ```
+    /// use questdb_confstr::parse_conf_str;
+    /// let config = parse_conf_str("http::port=9000;retries=lots;").unwrap();
+    /// assert_eq!(config.get_parsed::<u16>("port"), Ok(Some(9000)));
+    /// assert_eq!(config.get_parsed::<u16>("missing"), Ok(None));
+    /// let err = config.get_parsed::<u32>("retries").unwrap_err();
+    /// assert_eq!(
+    ///     err.to_string(),
+    ///     "invalid value for key \"retries\", expected a value of type `u32` at position 24");
+    /// ```
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Result<Option<T>, ValueError> {
+        self.get_with(
+            key,
+            || expected_type::<T>().into(),
+            |value| value.parse().ok(),
+        )
+    }
+
+    /// Get a boolean parameter, specified as `on`/`off` (or `true`/`false`).
+    pub fn get_bool(&self, key: &str) -> Result<Option<bool>, ValueError> {
+        self.get_with(key, || EXPECTED_BOOL.into(), parse_bool)
+    }
+
+    /// Get a duration parameter, specified as an integer number of milliseconds.
+    pub fn get_duration_ms(&self, key: &str) -> Result<Option<Duration>, ValueError> {
+        self.get_with(key, || EXPECTED_DURATION_MS.into(), parse_duration_ms)
+    }
+
+    /// Get a size parameter, specified as an integer number of bytes.
+    ///
+    /// The `k`, `m` and `g` suffixes (case-insensitive) multiply by
+    /// 1024, 1024² and 1024³ respectively.
+    pub fn get_bytes(&self, key: &str) -> Result<Option<u64>, ValueError> {
+        self.get_with(key, || EXPECTED_BYTES.into(), parse_bytes)
+    }
+}
@@ -0,0 +1,366 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::address::parse_address;
+use crate::secret::{push_str, wipe, wipe_bytes};
+use crate::write::check_ident;
+use crate::{is_invalid_value_char, ConfStr, Params, Position};
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::fmt::{Display, Formatter};
+
+/// Keys mapped to the URL's authority rather than to its query.
+const ADDR: &str = "addr";
+const USERNAME: &str = "username";
+const PASSWORD: &str = "password";
+
+/// The type of URL conversion error.
+///
+/// As with [`ErrorKind`](crate::ErrorKind), values are never included.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum UrlErrorKind {
+    /// The URL doesn't start with `scheme://`, or the scheme and the service
+    /// name can't be converted to each other.
+    InvalidScheme,
+
+    /// The host is missing, or `addr` is not a single `host[:port]` address.
+    InvalidAddress,
+
+    /// The URL has a path other than `/`.
+    UnsupportedPath,
+
+    /// The URL has a `#fragment`.
+    UnsupportedFragment,
+
+    /// A `%` is not followed by two hex digits, or the decoded bytes are not UTF-8.
+    InvalidPercentEncoding,
+
+    /// A query parameter's name is not a valid key.
+    InvalidKey,
+
+    /// A value contains a control character, or `username` is empty,
+    /// which a URL can't distinguish from an absent username.
+    InvalidValue(String),
+
+    /// A key is set more than once, e.g. `username` both in the query and
+    /// before the `@`, so it would not round-trip.
+    DuplicateKey(String),
+}
+
+impl Display for UrlErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            UrlErrorKind::InvalidScheme => write!(f, "invalid URL scheme"),
+            UrlErrorKind::InvalidAddress => {
+                write!(f, "invalid address, expected a single `host[:port]`")
+            }
+            UrlErrorKind::UnsupportedPath => write!(f, "URL paths are not supported"),
+            UrlErrorKind::UnsupportedFragment => write!(f, "URL fragments are not supported"),
+            UrlErrorKind::InvalidPercentEncoding => write!(f, "invalid percent-encoding"),
+            UrlErrorKind::InvalidKey => write!(f, "invalid key"),
+            UrlErrorKind::InvalidValue(key) => write!(f, "invalid value for key {:?}", key),
+            UrlErrorKind::DuplicateKey(key) => write!(f, "duplicate key {:?}", key),
+        }
+    }
+}
+
+/// Error converting between a [`ConfStr`] and a URL.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct UrlError {
+    kind: UrlErrorKind,
+    position: Option<Position>,
+}
+
+impl UrlError {
+    /// The type of error.
+    pub fn kind(&self) -> &UrlErrorKind {
+        &self.kind
+    }
+
+    /// Byte position in the URL, when converting from a URL.
+    pub fn position(&self) -> Option<Position> {
+        self.position
+    }
+}
+
+impl Display for UrlError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.kind)?;
+        if let Some(position) = self.position {
+            write!(f, " at position {}", position)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for UrlError {}
+
+fn url_err(kind: UrlErrorKind, position: Option<Position>) -> UrlError {
+    UrlError { kind, position }
+}
+
+fn is_scheme(service: &str) -> bool {
+    // Stricter than RFC 3986, which also allows `+`, `-` and `.`:
+    // The scheme has to be a valid service name too.
+    service.starts_with(|c: char| c.is_ascii_alphabetic())
+        && service
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn percent_encode(out: &mut String, s: &str) {
+    const HEX: &[u8; 16] = b"0123456789ABCDEF";
+    for b in s.bytes() {
+        let mut buf = [b, 0, 0];
+        let encoded = match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => &buf[..1],
+            _ => {
+                buf = [b'%', HEX[usize::from(b >> 4)], HEX[usize::from(b & 0xf)]];
+                &buf[..]
+            }
+        };
+        push_str(out, core::str::from_utf8(encoded).expect("ASCII"));
+    }
+}
+
+/// Decode `s`, which starts at `start` in the URL.
+/// In the query, `+` also stands for a space.
+fn percent_decode(s: &str, start: Position, query: bool) -> Result<String, UrlError> {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                let byte = s
+                    .get(i + 1..i + 3)
+                    .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                    .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+                match byte {
+                    Some(byte) => decoded.push(byte),
+                    None => {
+                        wipe_bytes(&mut decoded);
+                        return Err(url_err(
+                            UrlErrorKind::InvalidPercentEncoding,
+                            Some(start + i),
+                        ));
+                    }
+                }
+                i += 3;
+            }
+            b'+' if query => {
+                decoded.push(b' ');
+                i += 1;
+            }
+            b => {
+                decoded.push(b);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8(decoded).map_err(|err| {
+        wipe_bytes(&mut err.into_bytes());
+        url_err(UrlErrorKind::InvalidPercentEncoding, Some(start))
+    })
+}
+
+/// Insert a decoded param, rejecting duplicates and control characters.
+fn insert(
+    params: &mut Params,
+    key: &str,
+    mut value: String,
+    position: Position,
+) -> Result<(), UrlError> {
+    let err = if params.contains_key(key) {
+        Some(UrlErrorKind::DuplicateKey(key.to_string()))
+    } else if value.chars().any(is_invalid_value_char) {
+        Some(UrlErrorKind::InvalidValue(key.to_string()))
+    } else {
+        None
+    };
+    match err {
+        Some(kind) => {
+            wipe(&mut value);
+            Err(url_err(kind, Some(position)))
+        }
+        None => {
+            params.insert(key.to_string(), value);
+            Ok(())
+        }
+    }
+}
+
+impl ConfStr {
+    /// Convert to a URL, e.g. `https::addr=h:9000;username=u;password=p;retry_timeout=500;`
+    /// to `https://u:p@h:9000/?retry_timeout=500`.
+    ///
+    /// The service becomes the scheme; `addr` the host and port; `username` and
+    /// `password` the user info; and the other parameters the query, in order.
+    /// Everything but the host is percent-encoded.
+    ///
+    /// Fails if the configuration string would not convert back to an equal
+    /// one with [`ConfStr::from_url`], except for the order of `addr`,
+    /// `username` and `password`.
+    pub fn to_url(&self) -> Result<String, UrlError> {
+        if !is_scheme(self.service()) {
+            return Err(url_err(UrlErrorKind::InvalidScheme, None));
+        }
+        for (index, (key, value)) in self.params().iter().enumerate() {
+            if self.params.index_of(key) != Some(index) {
+                return Err(url_err(UrlErrorKind::DuplicateKey(key.to_string()), None));
+            }
+            if check_ident(key).is_err() {
+                return Err(url_err(UrlErrorKind::InvalidKey, None));
+            }
+            if value.chars().any(is_invalid_value_char) {
+                return Err(url_err(UrlErrorKind::InvalidValue(key.to_string()), None));
+            }
+        }
+        let addr = match self.get(ADDR) {
+            Some(addr) if parse_address(addr, Some(0)).is_ok() => addr,
+            _ => return Err(url_err(UrlErrorKind::InvalidAddress, None)),
+        };
+
+        let mut url = String::new();
+        push_str(&mut url, self.service());
+        push_str(&mut url, "://");
+        let username = self.get(USERNAME);
+        let password = self.get(PASSWORD);
+        if username == Some("") {
+            // `https://@h` or `https://:p@h` would convert back without a username.
+            return Err(url_err(
+                UrlErrorKind::InvalidValue(USERNAME.to_string()),
+                None,
+            ));
+        }
+        if username.is_some() || password.is_some() {
+            percent_encode(&mut url, username.unwrap_or(""));
+            if let Some(password) = password {
+                push_str(&mut url, ":");
+                percent_encode(&mut url, password);
+            }
+            push_str(&mut url, "@");
+        }
+        push_str(&mut url, addr);
+        push_str(&mut url, "/");
+        let query = self
+            .params()
+            .iter()
+            .filter(|(key, _)| ![ADDR, USERNAME, PASSWORD].contains(key));
+        for (index, (key, value)) in query.enumerate() {
+            push_str(&mut url, if index == 0 { "?" } else { "&" });
+            push_str(&mut url, key);
+            push_str(&mut url, "=");
+            percent_encode(&mut url, value);
+        }
+        Ok(url)
+    }
+
+    /// Parse a URL such as `https://u:p@h:9000/?retry_timeout=500`
+    /// into `https::addr=h:9000;username=u;password=p;retry_timeout=500;`.
+    ///
+    /// The inverse of [`ConfStr::to_url`]. The scheme is lowercased to give the
+    /// service name. In the query, `+` is decoded as a space and a parameter
+    /// without `=` has an empty value.
+    /// Error messages never include the URL's user info or values.
+    ///
+    /// ```
+    /// use questdb_confstr::ConfStr;
+    /// let config = ConfStr::from_url("https://admin:p%40ss@db:9000/?tls_verify=on").unwrap();
+    /// assert_eq!(
+    ///     config.to_conf_string().unwrap(),
+    ///     "https::addr=db:9000;username=admin;password=p@ss;tls_verify=on;");
+    /// assert_eq!(config.to_url().unwrap(), "https://admin:p%40ss@db:9000/?tls_verify=on");
+    /// ```
+    pub fn from_url(url: &str) -> Result<ConfStr, UrlError> {
+        let (service, rest) = url
+            .split_once("://")
+            .filter(|(scheme, _)| is_scheme(scheme))
+            .ok_or(url_err(UrlErrorKind::InvalidScheme, Some(0)))?;
+        let authority_start = service.len() + 3;
+        if let Some(p) = rest.find('#') {
+            return Err(url_err(
+                UrlErrorKind::UnsupportedFragment,
+                Some(authority_start + p),
+            ));
+        }
+        let authority_end = rest.find(['/', '?']).unwrap_or(rest.len());
+        let authority = &rest[..authority_end];
+        let (path, query) = match rest[authority_end..].split_once('?') {
+            Some((path, query)) => (path, Some(query)),
+            None => (&rest[authority_end..], None),
+        };
+        if !path.is_empty() && path != "/" {
+            return Err(url_err(
+                UrlErrorKind::UnsupportedPath,
+                Some(authority_start + authority_end),
+            ));
+        }
+
+        let mut params = Params::new();
+        let (userinfo, host) = match authority.rsplit_once('@') {
+            Some((userinfo, host)) => (Some(userinfo), host),
+            None => (None, authority),
+        };
+        let host_start = authority_start + authority.len() - host.len();
+        if parse_address(host, Some(0)).is_err() {
+            return Err(url_err(UrlErrorKind::InvalidAddress, Some(host_start)));
+        }
+        params.insert(ADDR.to_string(), host.to_string());
+        if let Some(userinfo) = userinfo {
+            let (username, password) = match userinfo.split_once(':') {
+                Some((username, password)) => (username, Some(password)),
+                None => (userinfo, None),
+            };
+            if !username.is_empty() {
+                let value = percent_decode(username, authority_start, false)?;
+                insert(&mut params, USERNAME, value, authority_start)?;
+            }
+            if let Some(password) = password {
+                let start = authority_start + username.len() + 1;
+                let value = percent_decode(password, start, false)?;
+                insert(&mut params, PASSWORD, value, start)?;
+            }
+        }
+
+        if let Some(query) = query {
+            let mut start = url.len() - query.len();
+            for pair in query.split('&') {
+                if !pair.is_empty() {
+                    let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+                    if check_ident(key).is_err() {
+                        return Err(url_err(UrlErrorKind::InvalidKey, Some(start)));
+                    }
+                    let value = percent_decode(value, start + key.len() + 1, true)?;
+                    insert(&mut params, key, value, start)?;
+                }
+                start += pair.len() + 1;
+            }
+        }
+        // Schemes are case-insensitive (RFC 3986, section 3.1), service names are not.
+        Ok(ConfStr::new(service.to_ascii_lowercase(), params))
+    }
+}
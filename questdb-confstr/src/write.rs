@@ -0,0 +1,81 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+use crate::{is_ident_char, is_invalid_value_char, parse_err, ErrorKind, ParsingError};
+use alloc::string::String;
+
+/// Check that `ident` would be parsed back as a single identifier.
+///
+/// On error, returns the kind and the byte offset within `ident`.
+pub(crate) fn check_ident(ident: &str) -> Result<(), (ErrorKind, usize)> {
+    let mut chars = ident.char_indices();
+    match chars.next() {
+        None => return Err((ErrorKind::ExpectedIdentifierNotEmpty, 0)),
+        Some((p, c)) if !is_ident_char(c) => return Err((ErrorKind::ExpectedIdentifierNot(c), p)),
+        Some(_) => {}
+    }
+    match chars.find(|(_, c)| !is_ident_char(*c)) {
+        Some((p, c)) => Err((ErrorKind::MustBeAlphanumeric(c), p)),
+        None => Ok(()),
+    }
+}
+
+pub(crate) fn write_ident(out: &mut String, ident: &str) -> Result<(), ParsingError> {
+    check_ident(ident).map_err(|(kind, p)| parse_err(kind, out.len() + p))?;
+    out.push_str(ident);
+    Ok(())
+}
+
+pub(crate) fn write_value(out: &mut String, value: &str) -> Result<(), ParsingError> {
+    for c in value.chars() {
+        if is_invalid_value_char(c) {
+            return Err(parse_err(ErrorKind::InvalidCharInValue(c), out.len()));
+        }
+        if c == ';' {
+            out.push(';');
+        }
+        out.push(c);
+    }
+    Ok(())
+}
+
+/// Append `service::key1=value1;key2=value2;` to `out`.
+///
+/// Errors are positioned at the byte offset in `out` where
+/// `parse_conf_str` would have rejected the output.
+pub(crate) fn write_conf_str<'a>(
+    out: &mut String,
+    service: &str,
+    params: impl IntoIterator<Item = (&'a str, &'a str)>,
+) -> Result<(), ParsingError> {
+    write_ident(out, service)?;
+    out.push_str("::");
+    for (key, value) in params {
+        write_ident(out, key)?;
+        out.push('=');
+        write_value(out, value)?;
+        out.push(';');
+    }
+    Ok(())
+}
@@ -0,0 +1,163 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+#![cfg(feature = "derive")]
+
+use questdb_confstr::{parse_conf_str, FieldError, FromConfStr, FromConfStrError};
+use std::time::Duration;
+
+fn parse_port(value: &str) -> Result<u16, String> {
+    match value.parse::<u16>() {
+        Ok(port) if port >= 1024 => Ok(port),
+        _ => Err("port must be between 1024 and 65535".to_string()),
+    }
+}
+
+fn default_max_buf_size() -> u64 {
+    100 * 1024 * 1024
+}
+
+#[derive(Debug, PartialEq, FromConfStr)]
+#[confstr(services("http", "https", "tcp", "tcps"))]
+struct ClientConfig {
+    addr: String,
+    #[confstr(parse_with = "parse_port")]
+    port: u16,
+    #[confstr(key = "user")]
+    username: Option<String>,
+    #[confstr(secret)]
+    password: Option<String>,
+    #[confstr(default)]
+    tls_verify: bool,
+    retry_timeout: Option<Duration>,
+    #[confstr(default = "default_max_buf_size")]
+    max_buf_size: u64,
+    #[confstr(services("tcp", "tcps"))]
+    token: Option<String>,
+    #[confstr(required)]
+    r#type: Option<String>,
+}
+
+#[test]
+fn derive_from_conf_str() -> Result<(), Box<dyn std::error::Error>> {
+    let conf_str = parse_conf_str(
+        "http::addr=localhost;port=9000;user=me;tls_verify=on;retry_timeout=500;type=x;",
+    )?;
+    let config = ClientConfig::try_from(&conf_str)?;
+    assert_eq!(
+        config,
+        ClientConfig {
+            addr: "localhost".to_string(),
+            port: 9000,
+            username: Some("me".to_string()),
+            password: None,
+            tls_verify: true,
+            retry_timeout: Some(Duration::from_millis(500)),
+            max_buf_size: 100 * 1024 * 1024,
+            token: None,
+            r#type: Some("x".to_string()),
+        }
+    );
+    Ok(())
+}
+
+#[test]
+fn derive_reports_all_errors() -> Result<(), Box<dyn std::error::Error>> {
+    let conf_str = parse_conf_str("udp::port=80;tls_verify=maybe;token=abc;max_buf_size=big;")?;
+    let err: FromConfStrError = ClientConfig::try_from(&conf_str).unwrap_err();
+    assert_eq!(
+        err.errors(),
+        &[
+            FieldError::UnsupportedService {
+                service: "udp".to_string(),
+                allowed: vec![
+                    "http".to_string(),
+                    "https".to_string(),
+                    "tcp".to_string(),
+                    "tcps".to_string()
+                ],
+                position: Some(0),
+            },
+            FieldError::MissingKey("addr".to_string()),
+            err.errors()[2].clone(),
+            err.errors()[3].clone(),
+            err.errors()[4].clone(),
+            FieldError::UnsupportedKey {
+                key: "token".to_string(),
+                service: "udp".to_string(),
+                position: Some(30),
+            },
+            FieldError::MissingKey("type".to_string()),
+        ]
+    );
+    let messages: Vec<String> = err.errors().iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        messages[2],
+        "invalid value for key \"port\", expected a valid value \
+         (port must be between 1024 and 65535) at position 10"
+    );
+    assert_eq!(
+        messages[3],
+        "invalid value for key \"tls_verify\", expected `on`, `off`, `true` or `false` at position 24"
+    );
+    assert_eq!(
+        messages[4],
+        "invalid value for key \"max_buf_size\", expected a value of type `u64` at position 53"
+    );
+    assert_eq!(
+        messages[5],
+        "key \"token\" is not supported for service \"udp\" at position 30"
+    );
+    assert!(err
+        .to_string()
+        .starts_with("unsupported service \"udp\", expected one of"));
+    Ok(())
+}
+
+#[test]
+fn derive_secret_hides_parse_with_message() -> Result<(), Box<dyn std::error::Error>> {
+    fn parse_secret(value: &str) -> Result<String, String> {
+        match value.len() >= 8 {
+            true => Ok(value.to_string()),
+            false => Err(format!("bad secret {}", value)),
+        }
+    }
+
+    #[derive(Debug, FromConfStr)]
+    struct Config {
+        #[confstr(secret, parse_with = "parse_secret")]
+        password: String,
+    }
+
+    let conf_str = parse_conf_str("http::password=correct-horse;")?;
+    assert_eq!(Config::try_from(&conf_str)?.password, "correct-horse");
+
+    let conf_str = parse_conf_str("http::password=hunter2;")?;
+    let err = Config::try_from(&conf_str).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for key \"password\", expected a valid value at position 15"
+    );
+    Ok(())
+}
@@ -0,0 +1,268 @@
+/*******************************************************************************
+ *     ___                  _   ____  ____
+ *    / _ \ _   _  ___  ___| |_|  _ \| __ )
+ *   | | | | | | |/ _ \/ __| __| | | |  _ \
+ *   | |_| | |_| |  __/\__ \ |_| |_| | |_) |
+ *    \__\_\\__,_|\___||___/\__|____/|____/
+ *
+ *  Copyright (c) 2014-2019 Appsicle
+ *  Copyright (c)  2019-2025 QuestDB
+ *
+ *  Licensed under the Apache License, Version 2.0 (the "License");
+ *  you may not use this file except in compliance with the License.
+ *  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ *  Unless required by applicable law or agreed to in writing, software
+ *  distributed under the License is distributed on an "AS IS" BASIS,
+ *  WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ *  See the License for the specific language governing permissions and
+ *  limitations under the License.
+ *
+ ******************************************************************************/
+
+#![cfg(feature = "serde")]
+
+use questdb_confstr::{from_conf_str, from_str, parse_conf_str, to_string};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct HttpConfig {
+    #[serde(rename = "$service")]
+    service: String,
+    addr: String,
+    username: Option<String>,
+    retry_timeout: Option<u64>,
+    #[serde(default)]
+    tls_verify: bool,
+    #[serde(default = "default_max_buf_size")]
+    max_buf_size: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    protocol_version: Option<ProtocolVersion>,
+}
+
+fn default_max_buf_size() -> usize {
+    104857600
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+#[serde(rename_all = "lowercase")]
+enum ProtocolVersion {
+    V1,
+    V2,
+}
+
+#[test]
+fn deserialize_struct() {
+    let config: HttpConfig = from_str(
+        "https::addr=localhost:9000;retry_timeout=10000;tls_verify=on;protocol_version=v2;",
+    )
+    .unwrap();
+    assert_eq!(
+        config,
+        HttpConfig {
+            service: "https".to_string(),
+            addr: "localhost:9000".to_string(),
+            username: None,
+            retry_timeout: Some(10000),
+            tls_verify: true,
+            max_buf_size: 104857600,
+            protocol_version: Some(ProtocolVersion::V2),
+        }
+    );
+}
+
+#[test]
+fn deserialize_without_service_field() {
+    #[derive(Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct Config {
+        port: u16,
+        ratio: f64,
+        mode: char,
+    }
+    let config: Config = from_str("tcp::port=9009;ratio=0.5;mode=x").unwrap();
+    assert_eq!(config.port, 9009);
+    assert_eq!(config.ratio, 0.5);
+    assert_eq!(config.mode, 'x');
+}
+
+#[test]
+fn deserialize_borrowed() {
+    #[derive(Deserialize)]
+    struct Config<'a> {
+        addr: &'a str,
+    }
+    let conf_str = parse_conf_str("http::addr=localhost:9000;").unwrap();
+    let config: Config = from_conf_str(&conf_str).unwrap();
+    assert_eq!(config.addr, "localhost:9000");
+}
+
+#[test]
+fn deserialize_map() {
+    let config: BTreeMap<String, String> = from_str("http::b=2;a=1;").unwrap();
+    assert_eq!(config.len(), 2);
+    assert_eq!(config["a"], "1");
+}
+
+#[test]
+fn deserialize_parse_error() {
+    let err = from_str::<HttpConfig>("http::addr").unwrap_err();
+    assert_eq!(err.position(), Some(10));
+    assert_eq!(
+        err.to_string(),
+        "incomplete key-value pair before end of input at position 10"
+    );
+}
+
+#[test]
+fn deserialize_bad_value() {
+    let err = from_str::<HttpConfig>("http::addr=h;retry_timeout=secret;").unwrap_err();
+    assert_eq!(err.key(), Some("retry_timeout"));
+    assert_eq!(err.position(), Some(27));
+    assert_eq!(
+        err.to_string(),
+        "invalid value for key \"retry_timeout\", expected a value of type `u64` at position 27"
+    );
+
+    let err = from_str::<HttpConfig>("http::addr=h;tls_verify=secret;").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for key \"tls_verify\", expected `on`, `off`, `true` or `false` at position 24"
+    );
+
+    let err = from_str::<HttpConfig>("http::addr=h;protocol_version=secret;").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for key \"protocol_version\", expected one of [\"v1\", \"v2\"] at position 30"
+    );
+}
+
+#[test]
+fn deserialize_unknown_field() {
+    let err = from_str::<HttpConfig>("http::addr=h;retry_timout=10;").unwrap_err();
+    assert_eq!(err.key(), None);
+    assert_eq!(err.position(), Some(13));
+    assert!(err
+        .to_string()
+        .starts_with("unknown field `retry_timout`, expected one of"));
+}
+
+#[test]
+fn deserialize_missing_field() {
+    let err = from_str::<HttpConfig>("http::retry_timeout=10;").unwrap_err();
+    assert_eq!(err.position(), None);
+    assert_eq!(err.to_string(), "missing field `addr`");
+}
+
+#[test]
+fn serialize_struct() {
+    let config = HttpConfig {
+        service: "ignored".to_string(),
+        addr: "localhost:9000".to_string(),
+        username: Some("user;name".to_string()),
+        retry_timeout: None,
+        tls_verify: false,
+        max_buf_size: 1024,
+        protocol_version: Some(ProtocolVersion::V1),
+    };
+    let conf_str = to_string("https", &config).unwrap();
+    assert_eq!(
+        conf_str,
+        "https::addr=localhost:9000;username=user;;name;tls_verify=off;\
+         max_buf_size=1024;protocol_version=v1;"
+    );
+
+    // Round trip through the parser and deserializer.
+    let parsed: HttpConfig = from_str(&conf_str).unwrap();
+    assert_eq!(parsed.service, "https");
+    assert_eq!(
+        parsed,
+        HttpConfig {
+            service: "https".to_string(),
+            ..config
+        }
+    );
+}
+
+#[test]
+fn serialize_map() {
+    let mut config = BTreeMap::new();
+    config.insert("b", 2.5);
+    config.insert("a", 1.0);
+    assert_eq!(to_string("tcp", &config).unwrap(), "tcp::a=1;b=2.5;");
+}
+
+#[test]
+fn serialize_rejects_nested() {
+    #[derive(Serialize)]
+    struct Nested {
+        addr: Vec<String>,
+    }
+    let err = to_string("http", &Nested { addr: vec![] }).unwrap_err();
+    assert_eq!(err.key(), Some("addr"));
+    assert_eq!(
+        err.to_string(),
+        "cannot serialize key \"addr\", nested sequences are not supported"
+    );
+
+    #[derive(Serialize)]
+    struct Inner {
+        x: u8,
+    }
+    #[derive(Serialize)]
+    struct Outer {
+        inner: Inner,
+    }
+    let err = to_string(
+        "http",
+        &Outer {
+            inner: Inner { x: 1 },
+        },
+    )
+    .unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "cannot serialize key \"inner\", nested structs are not supported"
+    );
+
+    let err = to_string("http", &42).unwrap_err();
+    assert_eq!(err.to_string(), "expected a struct or map at the top level");
+}
+
+#[test]
+fn serialize_validates_output() {
+    #[derive(Serialize)]
+    struct Config {
+        password: String,
+    }
+    let err = to_string(
+        "http",
+        &Config {
+            password: "a\nb".to_string(),
+        },
+    )
+    .unwrap_err();
+    assert_eq!(err.position(), Some(16));
+    assert_eq!(
+        err.to_string(),
+        "invalid char '\\n' in value at position 16"
+    );
+
+    let err = to_string("ht tp", &BTreeMap::<String, String>::new()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "must be alphanumeric, not ' ' at position 2"
+    );
+
+    let mut config = BTreeMap::new();
+    config.insert("a b", "1");
+    let err = to_string("http", &config).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "must be alphanumeric, not ' ' at position 7"
+    );
+}
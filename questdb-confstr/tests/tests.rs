@@ -22,8 +22,19 @@
  *
  ******************************************************************************/
 
-use questdb_confstr::{parse_conf_str, ErrorKind, ParsingError};
+use proptest::prelude::*;
+use questdb_confstr::{
+    default_port, parse_conf_str, parse_conf_str_bytes, parse_conf_str_bytes_with,
+    parse_conf_str_os, parse_conf_str_recovering, parse_conf_str_ref, parse_conf_str_ref_with,
+    parse_conf_str_with, repair, Address, ConfStr, ConfStrBuilder, DuplicateKeys, EnvError,
+    ErrorKind, FieldError, Fix, Hint, Host, InterpolationError, Location, Mask, MergeError, Params,
+    ParseOptions, ParsingError, Schema, ServiceSchema, UrlErrorKind, ValueType,
+};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::ffi::OsStr;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::time::Duration;
 
 #[test]
 fn empty() -> Result<(), ParsingError> {
@@ -77,12 +88,47 @@ fn duplicate_key() {
     assert_eq!(err.to_string(), "duplicate key \"host\" at position 21");
 }
 
+#[test]
+fn params_preserve_order() -> Result<(), ParsingError> {
+    let input = "http::zeta=1;alpha=2;mid=3;beta=4;";
+    let config = parse_conf_str(input)?;
+    let pairs: Vec<(&str, &str)> = config.params().iter().collect();
+    assert_eq!(
+        pairs,
+        vec![("zeta", "1"), ("alpha", "2"), ("mid", "3"), ("beta", "4")]
+    );
+    let keys: Vec<&str> = config.params().keys().collect();
+    assert_eq!(keys, vec!["zeta", "alpha", "mid", "beta"]);
+    assert_eq!(config.params().len(), 4);
+    Ok(())
+}
+
+#[test]
+fn params_insert_replaces_in_place() {
+    let mut params: Params = [
+        ("b".to_string(), "1".to_string()),
+        ("a".to_string(), "2".to_string()),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(
+        params.insert("b".to_string(), "3".to_string()),
+        Some("1".to_string())
+    );
+    assert_eq!(params.insert("c".to_string(), "4".to_string()), None);
+    let pairs: Vec<(&str, &str)> = params.iter().collect();
+    assert_eq!(pairs, vec![("b", "3"), ("a", "2"), ("c", "4")]);
+    assert_eq!(params.get("b"), Some("3"));
+    assert!(params.contains_key("c"));
+    assert!(!params.contains_key("d"));
+}
+
 #[test]
 fn key_can_start_with_number() -> Result<(), ParsingError> {
     let input = "https::123=456;";
     let config = parse_conf_str(input)?;
     assert_eq!(config.service(), "https");
-    let mut expected = HashMap::new();
+    let mut expected = Params::new();
     expected.insert("123".to_string(), "456".to_string());
     assert_eq!(config.params(), &expected);
     Ok(())
@@ -93,8 +139,9 @@ fn identifiers_can_contain_underscores() -> Result<(), ParsingError> {
     let input = "_A_::__x_Y__=42;";
     let config = parse_conf_str(input)?;
     assert_eq!(config.service(), "_A_");
-    let mut expected = HashMap::new();
+    let mut expected = Params::new();
     expected.insert("__x_Y__".to_string(), "42".to_string());
+    assert_eq!(config.params(), &expected);
     Ok(())
 }
 
@@ -362,3 +409,1197 @@ fn empty_value() -> Result<(), ParsingError> {
     assert_eq!(config.get("x"), Some(""));
     Ok(())
 }
+
+#[test]
+fn borrowed() -> Result<(), ParsingError> {
+    let input = "http::host=localhost;path=a;;b;;;empty=;";
+    let config = parse_conf_str_ref(input)?;
+    assert_eq!(config.service(), "http");
+    assert_eq!(config.get("host"), Some("localhost"));
+    assert_eq!(config.get("path"), Some("a;b;"));
+    assert_eq!(config.get("empty"), Some(""));
+    assert_eq!(config.get("missing"), None);
+    assert_eq!(
+        format!("{:?}", config),
+        "ConfStrRef { service: \"http\", .. }"
+    );
+
+    // Only the escaped value had to be copied.
+    let params = config.params();
+    assert!(matches!(params[0].1, Cow::Borrowed("localhost")));
+    assert!(matches!(params[1].1, Cow::Owned(_)));
+    assert!(matches!(params[2].1, Cow::Borrowed("")));
+
+    let owned: ConfStr = config.into();
+    assert_eq!(owned, parse_conf_str(input)?);
+    Ok(())
+}
+
+#[test]
+fn borrowed_errors() {
+    let err = parse_conf_str_ref("http::host=127.0.0.1;host=localhost;").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::DuplicateKey("host".to_string()));
+    assert_eq!(err.position(), 21);
+
+    let err = parse_conf_str_ref("http::host").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::IncompleteKeyValue);
+    assert_eq!(err.position(), 10);
+}
+
+#[test]
+fn spans() -> Result<(), ParsingError> {
+    let input = "http::host=localhost;path=a;;b;port=9000";
+    let config = parse_conf_str(input)?;
+    assert_eq!(config.service_span(), Some(0..4));
+    assert_eq!(config.key_span("host"), Some(6..10));
+    assert_eq!(config.value_span("host"), Some(11..20));
+    assert_eq!(config.key_span("path"), Some(21..25));
+    assert_eq!(config.value_span("path"), Some(26..30));
+    assert_eq!(&input[config.value_span("path").unwrap()], "a;;b");
+    assert_eq!(config.key_span("port"), Some(31..35));
+    assert_eq!(config.value_span("port"), Some(36..40));
+    assert_eq!(config.key_span("missing"), None);
+    assert_eq!(config.value_span("missing"), None);
+
+    let borrowed = parse_conf_str_ref(input)?;
+    assert_eq!(borrowed.service_span(), 0..4);
+    assert_eq!(borrowed.key_span("path"), Some(21..25));
+    assert_eq!(borrowed.value_span("path"), Some(26..30));
+    Ok(())
+}
+
+#[test]
+fn spans_empty_value_and_no_params() -> Result<(), ParsingError> {
+    let config = parse_conf_str("tcp::x=;")?;
+    assert_eq!(config.value_span("x"), Some(7..7));
+
+    let config = parse_conf_str("tcp")?;
+    assert_eq!(config.service_span(), Some(0..3));
+    Ok(())
+}
+
+#[test]
+fn no_spans_when_not_parsed() -> Result<(), ParsingError> {
+    let config = ConfStrBuilder::new("http").param("host", "h").build()?;
+    assert_eq!(config.service_span(), None);
+    assert_eq!(config.key_span("host"), None);
+    assert_eq!(config.value_span("host"), None);
+
+    // Spans do not affect equality.
+    assert_eq!(config, parse_conf_str("http::host=h")?);
+    Ok(())
+}
+
+#[test]
+fn recovering_collects_all_errors() {
+    let input = "http::host=localhost;port9000;a b=1;user=x\ty;=z;user=u;pass=p;;q;tls";
+    let (config, errors) = parse_conf_str_recovering(input);
+    assert_eq!(config.service(), "http");
+    let pairs: Vec<(&str, &str)> = config.params().iter().collect();
+    assert_eq!(
+        pairs,
+        vec![("host", "localhost"), ("user", "u"), ("pass", "p;q")]
+    );
+    let errors: Vec<(ErrorKind, usize)> = errors
+        .iter()
+        .map(|err| (err.kind().clone(), err.position()))
+        .collect();
+    assert_eq!(
+        errors,
+        vec![
+            (ErrorKind::BadSeparator(('=', ';')), 29),
+            (ErrorKind::MustBeAlphanumeric(' '), 31),
+            (ErrorKind::InvalidCharInValue('\t'), 42),
+            (ErrorKind::ExpectedIdentifierNot('='), 45),
+            (ErrorKind::IncompleteKeyValue, 68),
+        ]
+    );
+}
+
+#[test]
+fn recovering_duplicate_key_keeps_first() {
+    let (config, errors) = parse_conf_str_recovering("http::host=a;host=b;port=1");
+    assert_eq!(config.get("host"), Some("a"));
+    assert_eq!(config.get("port"), Some("1"));
+    assert_eq!(config.value_span("port"), Some(25..26));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(
+        errors[0].kind(),
+        ErrorKind::DuplicateKey("host".to_string())
+    );
+    assert_eq!(errors[0].position(), 13);
+}
+
+#[test]
+fn recovering_bad_service() {
+    let (config, errors) = parse_conf_str_recovering("http:/;host=localhost;");
+    assert_eq!(config.service(), "http");
+    assert_eq!(config.get("host"), Some("localhost"));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind(), ErrorKind::BadSeparator((':', '/')));
+    assert_eq!(errors[0].position(), 5);
+
+    let (config, errors) = parse_conf_str_recovering("協定;host=localhost;");
+    assert_eq!(config.service(), "");
+    assert_eq!(config.get("host"), Some("localhost"));
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].kind(), ErrorKind::ExpectedIdentifierNot('協'));
+}
+
+#[test]
+fn recovering_no_errors() -> Result<(), ParsingError> {
+    let input = "http::host=localhost;port=9000;";
+    let (config, errors) = parse_conf_str_recovering(input);
+    assert!(errors.is_empty());
+    assert_eq!(config, parse_conf_str(input)?);
+    Ok(())
+}
+
+#[test]
+fn typed_getters() -> Result<(), Box<dyn std::error::Error>> {
+    let input = "http::port=9000;ip=::1;tls_verify=on;auto_flush=false;\
+        retry_timeout=10000;max_buf_size=100m;init_buf_size=65536;";
+    let config = parse_conf_str(input)?;
+    assert_eq!(config.get_parsed::<u16>("port")?, Some(9000));
+    assert_eq!(config.get_parsed::<IpAddr>("ip")?, Some("::1".parse()?));
+    assert_eq!(config.get_parsed::<u16>("missing")?, None);
+    assert_eq!(config.get_bool("tls_verify")?, Some(true));
+    assert_eq!(config.get_bool("auto_flush")?, Some(false));
+    assert_eq!(config.get_bool("missing")?, None);
+    assert_eq!(
+        config.get_duration_ms("retry_timeout")?,
+        Some(Duration::from_secs(10))
+    );
+    assert_eq!(config.get_bytes("max_buf_size")?, Some(100 * 1024 * 1024));
+    assert_eq!(config.get_bytes("init_buf_size")?, Some(65536));
+    Ok(())
+}
+
+#[test]
+fn typed_getter_errors() -> Result<(), ParsingError> {
+    let input = "http::port=99999;tls_verify=yes;retry_timeout=10s;size=1t;password=secret";
+    let config = parse_conf_str(input)?;
+
+    let err = config.get_parsed::<u16>("port").unwrap_err();
+    assert_eq!(err.key(), "port");
+    assert_eq!(err.expected(), "a value of type `u16`");
+    assert_eq!(err.position(), Some(11));
+    assert_eq!(
+        err.to_string(),
+        "invalid value for key \"port\", expected a value of type `u16` at position 11"
+    );
+
+    let err = config.get_bool("tls_verify").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for key \"tls_verify\", expected `on`, `off`, `true` or `false` at position 28"
+    );
+
+    let err = config.get_duration_ms("retry_timeout").unwrap_err();
+    assert_eq!(err.expected(), "a duration in milliseconds");
+    assert_eq!(err.position(), Some(46));
+
+    let err = config.get_bytes("size").unwrap_err();
+    assert_eq!(
+        err.expected(),
+        "a size in bytes, optionally suffixed by `k`, `m` or `g`"
+    );
+
+    // The value is never echoed back.
+    let err = config.get_parsed::<u64>("password").unwrap_err();
+    assert!(!err.to_string().contains("secret"));
+
+    // No position without spans.
+    let config = ConfStrBuilder::new("http").param("port", "x").build()?;
+    assert_eq!(
+        config.get_parsed::<u16>("port").unwrap_err().position(),
+        None
+    );
+    Ok(())
+}
+
+#[test]
+fn serialize() -> Result<(), ParsingError> {
+    let input = "FTP::HOSTS=abc.com;;def.com;PORTS=9000;;;empty=;";
+    let config = parse_conf_str(input)?;
+    assert_eq!(config.to_conf_string()?, input);
+
+    let config = parse_conf_str("http")?;
+    assert_eq!(config.to_conf_string()?, "http::");
+    Ok(())
+}
+
+#[test]
+fn serialize_invalid_char_in_value() {
+    let mut params = Params::new();
+    params.insert("a".to_string(), "x;y".to_string());
+    params.insert("b".to_string(), "1\n2".to_string());
+    let config = ConfStr::new("http".to_string(), params);
+    let err = config.to_conf_string().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidCharInValue('\n'));
+    assert_eq!(err.position(), 16);
+    assert_eq!(
+        err.to_string(),
+        "invalid char '\\n' in value at position 16"
+    );
+}
+
+#[test]
+fn serialize_invalid_identifiers() {
+    let config = ConfStr::new("".to_string(), Params::new());
+    let err = config.to_conf_string().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ExpectedIdentifierNotEmpty);
+    assert_eq!(err.position(), 0);
+
+    let config = ConfStr::new("ht tp".to_string(), Params::new());
+    let err = config.to_conf_string().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MustBeAlphanumeric(' '));
+    assert_eq!(err.position(), 2);
+
+    let mut params = Params::new();
+    params.insert("=x".to_string(), "1".to_string());
+    let config = ConfStr::new("http".to_string(), params);
+    let err = config.to_conf_string().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ExpectedIdentifierNot('='));
+    assert_eq!(err.position(), 6);
+}
+
+#[test]
+fn builder() -> Result<(), ParsingError> {
+    let config = ConfStrBuilder::new("http")
+        .param("host", "localhost")
+        .param("port", "9000")
+        .param("path", "a;b")
+        .build()?;
+    assert_eq!(config.service(), "http");
+    assert_eq!(config.get("port"), Some("9000"));
+    assert_eq!(config.get("path"), Some("a;b"));
+    assert_eq!(
+        config.to_conf_string()?,
+        "http::host=localhost;port=9000;path=a;;b;"
+    );
+
+    let config = ConfStrBuilder::new("tcp").build()?;
+    assert_eq!(config.service(), "tcp");
+    assert!(config.params().is_empty());
+    Ok(())
+}
+
+#[test]
+fn builder_errors() {
+    let err = ConfStrBuilder::new("").build().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ExpectedIdentifierNotEmpty);
+    assert_eq!(err.position(), 0);
+
+    let err = ConfStrBuilder::new("http:").build().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MustBeAlphanumeric(':'));
+    assert_eq!(err.position(), 4);
+
+    let err = ConfStrBuilder::new("http")
+        .param("host", "localhost")
+        .param("h st", "x")
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::MustBeAlphanumeric(' '));
+    assert_eq!(err.position(), 22);
+
+    let err = ConfStrBuilder::new("http")
+        .param("x", "a\tb")
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidCharInValue('\t'));
+    assert_eq!(err.position(), 9);
+
+    let err = ConfStrBuilder::new("http")
+        .param("host", "127.0.0.1")
+        .param("host", "localhost")
+        .build()
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::DuplicateKey("host".to_string()));
+    assert_eq!(err.position(), 21);
+    assert_eq!(err.to_string(), "duplicate key \"host\" at position 21");
+}
+
+fn client_schema() -> Schema {
+    let service = |name: &str| {
+        ServiceSchema::new(name)
+            .required("addr", ValueType::String)
+            .optional("auto_flush", ValueType::Bool)
+            .optional("auto_flush_rows", ValueType::Integer)
+            .with_default("retry_timeout", ValueType::DurationMs, "10000")
+            .with_default("max_buf_size", ValueType::Bytes, "100m")
+    };
+    Schema::new()
+        .service(service("http"))
+        .service(service("tcp"))
+}
+
+#[test]
+fn schema_validate() -> Result<(), Box<dyn std::error::Error>> {
+    let schema = client_schema();
+    let config = parse_conf_str("http::addr=localhost;auto_flush=off;auto_flush_rows=-1;")?;
+    schema.validate(&config)?;
+
+    let config = schema.apply_defaults(&config);
+    assert_eq!(config.get("retry_timeout"), Some("10000"));
+    assert_eq!(config.get_bytes("max_buf_size")?, Some(100 * 1024 * 1024));
+    assert_eq!(config.key_span("addr"), Some(6..10));
+    assert_eq!(config.key_span("retry_timeout"), None);
+    schema.validate(&config)?;
+
+    let config = parse_conf_str("http::addr=localhost;retry_timeout=500;")?;
+    let config = schema.apply_defaults(&config);
+    assert_eq!(config.get("retry_timeout"), Some("500"));
+    Ok(())
+}
+
+#[test]
+fn schema_validate_errors() -> Result<(), ParsingError> {
+    let schema = client_schema();
+    let config = parse_conf_str("udp::addr=localhost;")?;
+    let err = schema.validate(&config).unwrap_err();
+    assert_eq!(
+        err.errors(),
+        &[FieldError::UnsupportedService {
+            service: "udp".to_string(),
+            allowed: vec!["http".to_string(), "tcp".to_string()],
+            position: Some(0),
+        }]
+    );
+
+    let config = parse_conf_str("tcp::retry_timout=500;auto_flush=maybe;max_buf_size=lots;")?;
+    let err = schema.validate(&config).unwrap_err();
+    assert_eq!(
+        err.errors()
+            .iter()
+            .map(|e| (e.key(), e.position()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some("retry_timout"), Some(5)),
+            (Some("auto_flush"), Some(33)),
+            (Some("max_buf_size"), Some(52)),
+            (Some("addr"), None),
+        ]
+    );
+    assert_eq!(
+        err.to_string(),
+        "key \"retry_timout\" is not supported for service \"tcp\" at position 5; \
+         invalid value for key \"auto_flush\", expected `on`, `off`, `true` or `false` at position 33; \
+         invalid value for key \"max_buf_size\", expected a size in bytes, \
+         optionally suffixed by `k`, `m` or `g` at position 52; \
+         missing required key \"addr\""
+    );
+
+    let config = parse_conf_str("tcp::addr=localhost;auto_flush_rows=many;")?;
+    let err = schema.validate(&config).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid value for key \"auto_flush_rows\", expected a value of type `i64` at position 36"
+    );
+
+    // Errors in repeated keys point at the offending occurrence.
+    let options = ParseOptions::new().duplicate_keys(DuplicateKeys::Collect);
+    let config = parse_conf_str_with(
+        "tcp::addr=a;auto_flush=on;auto_flush=maybe;bogus=1;bogus=2;",
+        &options,
+    )?;
+    let err = schema.validate(&config).unwrap_err();
+    assert_eq!(
+        err.errors()
+            .iter()
+            .map(|e| (e.key(), e.position()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some("auto_flush"), Some(37)),
+            (Some("bogus"), Some(43)),
+            (Some("bogus"), Some(51)),
+        ]
+    );
+    Ok(())
+}
+
+fn vars() -> HashMap<String, String> {
+    HashMap::from([
+        ("HOST".to_string(), "localhost".to_string()),
+        ("PASSWORD".to_string(), "pa;ss$".to_string()),
+        ("EMPTY".to_string(), "".to_string()),
+    ])
+}
+
+#[test]
+fn interpolate() -> Result<(), Box<dyn std::error::Error>> {
+    let config = parse_conf_str(
+        "http::addr=${HOST}:9000;password=${PASSWORD};user=${USER_NAME:-admin};\
+         token=${EMPTY:-none};empty=${EMPTY};literal=$$HOST $5 a;;b;",
+    )?;
+    let expanded = config.interpolate_with(&vars())?;
+    assert_eq!(expanded.get("addr"), Some("localhost:9000"));
+    assert_eq!(expanded.get("password"), Some("pa;ss$"));
+    assert_eq!(expanded.get("user"), Some("admin"));
+    assert_eq!(expanded.get("token"), Some("none"));
+    assert_eq!(expanded.get("empty"), Some(""));
+    assert_eq!(expanded.get("literal"), Some("$HOST $5 a;b"));
+    assert_eq!(expanded.key_span("password"), config.key_span("password"));
+    assert_eq!(
+        expanded.to_conf_string()?,
+        "http::addr=localhost:9000;password=pa;;ss$;user=admin;\
+         token=none;empty=;literal=$HOST $5 a;;b;"
+    );
+
+    let config = parse_conf_str("http::path=${PATH};")?;
+    assert_eq!(
+        config.interpolate()?.get("path"),
+        std::env::var("PATH").ok().as_deref()
+    );
+    Ok(())
+}
+
+#[test]
+fn interpolate_errors() -> Result<(), ParsingError> {
+    let config = parse_conf_str("http::addr=x;password=a;;b${SECRET_PASSWORD};")?;
+    let err = config.interpolate_with(&vars()).unwrap_err();
+    assert_eq!(
+        err,
+        InterpolationError::UnsetVariable {
+            key: "password".to_string(),
+            variable: "SECRET_PASSWORD".to_string(),
+            position: Some(26),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "unset variable \"SECRET_PASSWORD\" in value for key \"password\" at position 26"
+    );
+
+    let config = parse_conf_str("http::addr=${HOST;")?;
+    let err = config.interpolate_with(&vars()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "unterminated \"${\" in value for key \"addr\" at position 11"
+    );
+
+    let config = parse_conf_str("http::addr=${HO-ST};")?;
+    let err = config.interpolate_with(&vars()).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid variable name in value for key \"addr\" at position 11"
+    );
+
+    let config = ConfStr::new(
+        "http".to_string(),
+        [("addr".to_string(), "${}".to_string())]
+            .into_iter()
+            .collect(),
+    );
+    let err = config.interpolate_with(&vars()).unwrap_err();
+    assert_eq!(err.key(), "addr");
+    assert_eq!(err.position(), None);
+    Ok(())
+}
+
+#[test]
+fn redacted() -> Result<(), ParsingError> {
+    let config = parse_conf_str(
+        "tcp::addr=localhost:9009;username=testUser1;token=5UjEMuA0Pj5pjK8a;;e;\
+         token_x=fLKYEaoEb9lrn3nkwLDA-M_xnuFOdSt9y0Z7_vWSHLU;\
+         token_y=Dt5tbS1dEDMSYfym3fgMv0B99szno-dFc1rYF9t0aac;",
+    )?;
+    let expected = "tcp::addr=localhost:9009;username=testUser1;token=****;\
+                    token_x=****;token_y=****;";
+    assert_eq!(config.redacted().to_string(), expected);
+    assert_eq!(config.to_string(), expected);
+    assert_eq!(format!("{:?}", config.redacted()), expected);
+
+    assert_eq!(
+        config
+            .redacted()
+            .sensitive_key("username")
+            .mask(Mask::RevealLast(4))
+            .to_string(),
+        "tcp::addr=localhost:9009;username=****ser1;token=****8a;;e;\
+         token_x=****SHLU;token_y=****0aac;"
+    );
+
+    let config = parse_conf_str("http::password=abc;tls_roots_password=abcdefgh;")?;
+    assert_eq!(
+        config.redacted().mask(Mask::RevealLast(2)).to_string(),
+        "http::password=****;tls_roots_password=****gh;"
+    );
+    assert_eq!(
+        config.redacted().mask(Mask::RevealLast(0)).to_string(),
+        "http::password=****;tls_roots_password=****;"
+    );
+
+    let config = ConfStr::new(
+        "http".to_string(),
+        [("addr".to_string(), "a\tb".to_string())]
+            .into_iter()
+            .collect(),
+    );
+    assert_eq!(config.to_string(), "http::addr=a\\tb;");
+    Ok(())
+}
+
+#[test]
+fn secret() -> Result<(), ParsingError> {
+    let config = parse_conf_str("http::username=admin;password=a;;b;api_key=k;")?;
+    let password = config.get_secret("password").unwrap();
+    assert_eq!(password.expose_secret(), "a;b");
+    assert_eq!(format!("{:?}", password), "Secret(****)");
+    assert_eq!(
+        format!("{:?}", config.get_secret("password")),
+        "Some(Secret(****))"
+    );
+    assert!(config.get_secret("token").is_none());
+    // The caller decides which keys are sensitive.
+    assert_eq!(config.get_secret("api_key").unwrap().expose_secret(), "k");
+    Ok(())
+}
+
+#[test]
+fn merge() -> Result<(), Box<dyn std::error::Error>> {
+    let defaults = parse_conf_str("http::retry_timeout=10000;auto_flush=on;")?;
+    let profile = parse_conf_str("http::addr=prod:9000;username=ingest;")?.with_source("profile");
+    let env = parse_conf_str("http::retry_timeout=500;")?.with_source("env");
+    let cli = parse_conf_str("http::auto_flush=off;addr=localhost:9000;")?.with_source("cli");
+
+    let config = ConfStr::merge([&defaults, &profile, &env, &cli])?;
+    assert_eq!(
+        config.to_conf_string()?,
+        "http::retry_timeout=500;auto_flush=off;addr=localhost:9000;username=ingest;"
+    );
+    assert_eq!(config.source_of("retry_timeout"), Some("env"));
+    assert_eq!(config.source_of("auto_flush"), Some("cli"));
+    assert_eq!(config.source_of("addr"), Some("cli"));
+    assert_eq!(config.source_of("username"), Some("profile"));
+    assert_eq!(config.source_of("missing"), None);
+    assert_eq!(config.key_span("addr"), None);
+
+    let config = defaults.overlay(&env)?;
+    assert_eq!(config.get("retry_timeout"), Some("500"));
+    assert_eq!(config.source_of("retry_timeout"), Some("env"));
+    assert_eq!(config.source_of("auto_flush"), None);
+
+    let defaults = defaults.with_source("defaults");
+    let config = env.overlay(&defaults)?;
+    assert_eq!(config.get("retry_timeout"), Some("10000"));
+    assert_eq!(config.source_of("retry_timeout"), Some("defaults"));
+    Ok(())
+}
+
+#[test]
+fn merge_errors() -> Result<(), ParsingError> {
+    let http = parse_conf_str("http::addr=localhost:9000;")?.with_source("defaults");
+    let tcp = parse_conf_str("tcp::addr=localhost:9009;")?.with_source("env");
+    let err = http.overlay(&tcp).unwrap_err();
+    assert_eq!(
+        err,
+        MergeError::ServiceMismatch {
+            expected: "http".to_string(),
+            found: "tcp".to_string(),
+            source: Some("env".to_string()),
+        }
+    );
+    assert_eq!(
+        err.to_string(),
+        "service mismatch, expected \"http\", found \"tcp\" in \"env\""
+    );
+    assert_eq!(ConfStr::merge([]), Err(MergeError::NoLayers));
+    Ok(())
+}
+
+#[test]
+fn from_env() -> Result<(), Box<dyn std::error::Error>> {
+    let var = "QDB_CONFSTR_TEST_FROM_ENV";
+    std::env::set_var(
+        var,
+        "http::addr=localhost:9000;username=admin;password=quest;",
+    );
+    let config = ConfStr::from_env(var)?;
+    assert_eq!(config.get("password"), Some("quest"));
+    assert_eq!(config.source_of("password"), Some(var));
+    assert_eq!(config.key_span("password"), Some(41..49));
+
+    std::env::set_var(format!("{}__PASSWORD", var), "s3cr;;et");
+    std::env::set_var(format!("{}__TOKEN", var), "abc");
+    let config = ConfStr::from_env(var)?;
+    assert_eq!(
+        config.to_conf_string()?,
+        "http::addr=localhost:9000;username=admin;password=s3cr;;et;token=abc;"
+    );
+    assert_eq!(config.source_of("addr"), Some(var));
+    assert_eq!(
+        config.source_of("password"),
+        Some("QDB_CONFSTR_TEST_FROM_ENV__PASSWORD")
+    );
+    assert_eq!(
+        config.source_of("token"),
+        Some("QDB_CONFSTR_TEST_FROM_ENV__TOKEN")
+    );
+
+    std::env::set_var(format!("{}__PASSWORD", var), "s3cr;et");
+    let err = ConfStr::from_env(var).unwrap_err();
+    assert_eq!(err.variable(), "QDB_CONFSTR_TEST_FROM_ENV__PASSWORD");
+    assert_eq!(
+        err.to_string(),
+        "invalid environment variable \"QDB_CONFSTR_TEST_FROM_ENV__PASSWORD\": \
+         invalid char ';' in value at position 4"
+    );
+    std::env::remove_var(format!("{}__PASSWORD", var));
+
+    std::env::set_var(format!("{}__BAD-KEY", var), "x");
+    let err = ConfStr::from_env(var).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "invalid key in environment variable \"QDB_CONFSTR_TEST_FROM_ENV__BAD-KEY\""
+    );
+    std::env::remove_var(format!("{}__BAD-KEY", var));
+
+    std::env::set_var(var, "http::password=a\tb;");
+    let err = ConfStr::from_env(var).unwrap_err();
+    assert!(matches!(err, EnvError::Parsing { .. }));
+    assert_eq!(
+        err.to_string(),
+        "invalid environment variable \"QDB_CONFSTR_TEST_FROM_ENV\": \
+         invalid char '\\t' in value at position 16"
+    );
+
+    let err = ConfStr::from_env("QDB_CONFSTR_TEST_UNSET").unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "environment variable \"QDB_CONFSTR_TEST_UNSET\" is not set"
+    );
+    Ok(())
+}
+
+#[test]
+fn render_masks_values() {
+    let render = |input: &str| parse_conf_str(input).unwrap_err().render(input).to_string();
+    assert_eq!(
+        render("http::password=a;;b;user=x\ty;"),
+        "invalid char '\\t' in value at position 26\n\
+         http::password=****;user=***;\n\
+         \x20                         ^"
+    );
+    assert_eq!(
+        render("http::user=Øyvind;pass word=1;"),
+        "must be alphanumeric, not ' ' at position 23\n\
+         http::user=******************;\n\
+         \x20                     ^"
+    );
+    assert_eq!(
+        render("http;password=secret"),
+        "bad separator, expected ':' got ';' at position 4\n\
+         http;password=******\n\
+         \x20   ^\n\
+         help: did you mean `::`? Separate the service name from the parameters \
+         with `::`, e.g. `http::addr=localhost:9000;`"
+    );
+    // Text after a lone `;` in a password stays masked up to the next `key=`.
+    assert_eq!(
+        render("http::password=ab;cdef;port=1;"),
+        "bad separator, expected '=' got ';' at position 22\n\
+         http::password=*******;port=*;\n\
+         \x20                     ^\n\
+         help: if the previous value contains a `;`, escape it as `;;`"
+    );
+    assert_eq!(
+        render("http::password=se;cret"),
+        "incomplete key-value pair before end of input at position 22\n\
+         http::password=*******\n\
+         \x20                     ^\n\
+         help: if the previous value contains a `;`, escape it as `;;`"
+    );
+    // Secrets in malformed input stay masked, too.
+    assert!(render("https://admin:hunter2@db:9000/").contains("\nhttps://*************@db:9000/\n"));
+    assert_eq!(
+        render("https://admin:hunter2@db:9000/?token=abc&x=1"),
+        "bad separator, expected ':' got '/' at position 6\n\
+         https://*************@db:9000/?token=***&x=*\n\
+         \x20     ^\n\
+         help: this looks like a URL: write `http::addr=localhost:9000;` instead, \
+         or convert it with `ConfStr::from_url`"
+    );
+    assert_eq!(
+        render("http::password hunter2;"),
+        "must be alphanumeric, not ' ' at position 14\n\
+         http::password********;\n\
+         \x20             ^"
+    );
+    assert_eq!(
+        render("http::password;hunter2;port=1;"),
+        "bad separator, expected '=' got ';' at position 14\n\
+         http::password********;port=*;\n\
+         \x20             ^"
+    );
+    assert_eq!(
+        render("tcp::addr"),
+        "incomplete key-value pair before end of input at position 9\n\
+         tcp::addr\n\
+         \x20        ^"
+    );
+}
+
+#[test]
+fn parse_hints() {
+    let hint = |input: &str| parse_conf_str(input).unwrap_err().hint();
+    assert_eq!(hint("http:addr=localhost:9000;"), Some(Hint::DoubleColon));
+    assert_eq!(hint("http;addr=localhost:9000;"), Some(Hint::DoubleColon));
+    assert_eq!(hint("https://localhost:9000"), Some(Hint::LooksLikeUrl));
+    assert_eq!(hint("http-x::a=1;"), None);
+    assert_eq!(hint("http.s::a=1;"), None);
+    assert_eq!(hint("http::password=a;b;c=1;"), Some(Hint::EscapeSemicolon));
+    assert_eq!(hint("http::password=a;b"), Some(Hint::EscapeSemicolon));
+    assert_eq!(
+        hint("http::password=a;b c;d=1;"),
+        Some(Hint::EscapeSemicolon)
+    );
+    assert_eq!(hint("http::password=a;b c=1;"), None);
+    assert_eq!(hint("http::addr=x;po rt=1;"), None);
+    assert_eq!(hint("http::a=1;=2;"), None);
+    assert_eq!(hint("http::a=1;\nb=2;"), None);
+    assert_eq!(hint("http::addr"), None);
+    assert_eq!(hint("http::addr=a\tb;"), None);
+    assert_eq!(hint("http::addr=a;addr=b;"), None);
+
+    let err = parse_conf_str("http::password=a;b;c=1;").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::BadSeparator(('=', ';')));
+    assert_eq!(
+        err.hint().unwrap().to_string(),
+        "if the previous value contains a `;`, escape it as `;;`"
+    );
+}
+
+#[test]
+fn repair_fixes() {
+    let input = "  \"http:addr = localhost:9000 ; password = a;b ;c;user=admin\"\n";
+    let (repaired, fixes) = repair(input).unwrap();
+    assert_eq!(
+        repaired,
+        "http::addr=localhost:9000;password=a;;b ;;c;user=admin"
+    );
+    let password = |position| Fix::EscapedSemicolon {
+        key: "password".to_string(),
+        position,
+    };
+    assert_eq!(
+        fixes,
+        vec![
+            Fix::RemovedWhitespace { position: 0 },
+            Fix::RemovedQuotes { position: 2 },
+            Fix::DoubledColon { position: 7 },
+            Fix::RemovedWhitespace { position: 12 },
+            Fix::RemovedWhitespace { position: 14 },
+            Fix::RemovedWhitespace { position: 29 },
+            Fix::RemovedWhitespace { position: 31 },
+            Fix::RemovedWhitespace { position: 40 },
+            Fix::RemovedWhitespace { position: 42 },
+            password(44),
+            password(47),
+            Fix::RemovedWhitespace { position: 61 },
+        ]
+    );
+    assert!(fixes.iter().all(|fix| !fix.to_string().contains("admin")));
+
+    let input = "http::addr=localhost:9000 ;";
+    assert_eq!(repair(input), Some((input.to_string(), vec![])));
+
+    // A lone `;` is only escaped in the values of sensitive keys.
+    assert_eq!(repair("http::addr=a;b;"), None);
+    assert_eq!(repair("https://localhost:9000"), None);
+    assert_eq!(repair("http::password=a\tb;"), None);
+}
+
+#[test]
+fn locations() {
+    let input = "http::user=Zoë😀;pass word=1;";
+    let err = parse_conf_str(input).unwrap_err();
+    let location = err.location(input);
+    assert_eq!(location.byte(), 24);
+    assert_eq!(location.char(), 20);
+    assert_eq!(location.line(), 1);
+    assert_eq!(location.column(), 21);
+    assert_eq!(location.utf16_column(), 22);
+
+    let input = "ab\r\ncdé😀x";
+    let location = Location::new(input, input.find('x').unwrap());
+    assert_eq!(location.char(), 8);
+    assert_eq!(location.line(), 2);
+    assert_eq!(location.column(), 5);
+    assert_eq!(location.utf16_column(), 6);
+
+    // Clamped to a char boundary, then to the end of the input.
+    assert_eq!(Location::new("é", 1), Location::new("é", 0));
+    assert_eq!(Location::new("ab", 9).byte(), 2);
+    assert_eq!(Location::new("ab", 9).column(), 3);
+}
+
+#[test]
+fn parse_bytes() -> Result<(), ParsingError> {
+    let config = parse_conf_str_bytes("http::user=Øyvind;".as_bytes())?;
+    assert_eq!(config.get("user"), Some("Øyvind"));
+
+    let err = parse_conf_str_bytes(b"http::user=a\xe2\x82;").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidUtf8);
+    assert_eq!(err.position(), 12);
+    assert_eq!(err.to_string(), "invalid UTF-8 sequence at position 12");
+
+    let options = ParseOptions::new().duplicate_keys(DuplicateKeys::Collect);
+    let config = parse_conf_str_bytes_with(b"tcp::addr=a;addr=b;", &options)?;
+    assert_eq!(config.get_all("addr").collect::<Vec<_>>(), ["a", "b"]);
+
+    let config = parse_conf_str_os(OsStr::new("tcp::addr=a;"))?;
+    assert_eq!(config.get("addr"), Some("a"));
+    Ok(())
+}
+
+#[cfg(unix)]
+#[test]
+fn parse_os_invalid_utf8() {
+    use std::os::unix::ffi::OsStrExt;
+
+    let input = OsStr::from_bytes(b"http::\xffuser=a;");
+    let err = parse_conf_str_os(input).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidUtf8);
+    assert_eq!(err.position(), 6);
+
+    let var = "QDB_CONFSTR_TEST_OS_INVALID_UTF8";
+    std::env::set_var(var, input);
+    let err = ConfStr::from_env(var).unwrap_err();
+    assert_eq!(
+        err.to_string(),
+        "environment variable \"QDB_CONFSTR_TEST_OS_INVALID_UTF8\" is not valid unicode"
+    );
+
+    std::env::set_var(var, "http::user=a;");
+    std::env::set_var(format!("{}__PASSWORD", var), OsStr::from_bytes(b"ab\xff"));
+    let err = ConfStr::from_env(var).unwrap_err();
+    assert!(matches!(err, EnvError::NotUnicode { .. }));
+    assert_eq!(err.variable(), "QDB_CONFSTR_TEST_OS_INVALID_UTF8__PASSWORD");
+    std::env::remove_var(format!("{}__PASSWORD", var));
+}
+
+#[test]
+fn duplicate_keys_last_wins() -> Result<(), ParsingError> {
+    let input = "http::addr=a:9000;port=1;addr=b;;c:9000;";
+    let options = ParseOptions::new().duplicate_keys(DuplicateKeys::LastWins);
+    let config = parse_conf_str_with(input, &options)?;
+    assert_eq!(
+        config.params().iter().collect::<Vec<_>>(),
+        vec![("addr", "b;c:9000"), ("port", "1")]
+    );
+    assert_eq!(config.key_span("addr"), Some(25..29));
+    assert_eq!(config.value_span("addr"), Some(30..39));
+    assert_eq!(config.to_conf_string()?, "http::addr=b;;c:9000;port=1;");
+    Ok(())
+}
+
+#[test]
+fn duplicate_keys_collect() -> Result<(), ParsingError> {
+    let input = "http::addr=a:9000;port=1;addr=b:9000;";
+    let options = ParseOptions::new().duplicate_keys(DuplicateKeys::Collect);
+    let config = parse_conf_str_with(input, &options)?;
+    assert_eq!(config.get("addr"), Some("a:9000"));
+    assert_eq!(
+        config.get_all("addr").collect::<Vec<_>>(),
+        vec!["a:9000", "b:9000"]
+    );
+    assert_eq!(config.get_all("port").collect::<Vec<_>>(), vec!["1"]);
+    assert_eq!(config.get_all("host").count(), 0);
+    assert_eq!(config.params().len(), 3);
+    assert_eq!(config.to_conf_string()?, input);
+    assert_eq!(parse_conf_str_with(input, &options)?, config);
+    assert_eq!(
+        parse_conf_str(input).unwrap_err().kind(),
+        ErrorKind::DuplicateKey("addr".to_string())
+    );
+
+    let config_ref = parse_conf_str_ref_with(input, &options)?;
+    assert_eq!(
+        config_ref.get_all("addr").collect::<Vec<_>>(),
+        vec!["a:9000", "b:9000"]
+    );
+
+    let vars = HashMap::from([("B".to_string(), "b:9000".to_string())]);
+    let config = parse_conf_str_with("http::addr=a:9000;addr=${B};addr=${C};", &options)?;
+    let err = config.interpolate_with(&vars).unwrap_err();
+    assert_eq!(err.position(), Some(33));
+    let config = parse_conf_str_with("http::addr=a:9000;addr=${B};", &options)?;
+    assert_eq!(
+        config
+            .interpolate_with(&vars)
+            .unwrap()
+            .get_all("addr")
+            .collect::<Vec<_>>(),
+        vec!["a:9000", "b:9000"]
+    );
+
+    // A layer's values replace all previous values of the key.
+    let config = parse_conf_str_with(input, &options)?;
+    let overrides = parse_conf_str_with("http::addr=c:9000;addr=d:9000;", &options)?;
+    let config = config.overlay(&overrides).unwrap();
+    assert_eq!(
+        config.params().iter().collect::<Vec<_>>(),
+        vec![("addr", "c:9000"), ("addr", "d:9000"), ("port", "1")]
+    );
+    Ok(())
+}
+
+#[test]
+fn addresses() -> Result<(), Box<dyn std::error::Error>> {
+    let config = parse_conf_str(
+        "http::addr=db-1.example.com, 10.0.0.2:9001,[2001:db8::1],[::1]:9002,my_db:1;",
+    )?;
+    let addresses: Vec<Address> = config.get_addresses("addr")?.unwrap().into_iter().collect();
+    assert_eq!(
+        addresses.iter().map(|a| a.host()).collect::<Vec<_>>(),
+        vec![
+            &Host::Name("db-1.example.com".to_string()),
+            &Host::Ipv4(Ipv4Addr::new(10, 0, 0, 2)),
+            &Host::Ipv6("2001:db8::1".parse::<Ipv6Addr>()?),
+            &Host::Ipv6(Ipv6Addr::LOCALHOST),
+            &Host::Name("my_db".to_string()),
+        ]
+    );
+    assert_eq!(
+        addresses.iter().map(|a| a.port()).collect::<Vec<_>>(),
+        vec![9000, 9001, 9000, 9002, 1]
+    );
+    assert_eq!(addresses[2].to_string(), "[2001:db8::1]:9000");
+    assert_eq!(
+        addresses[3].to_socket_addrs()?.collect::<Vec<_>>(),
+        vec!["[::1]:9002".parse::<SocketAddr>()?]
+    );
+
+    let config = parse_conf_str("tcps::addr=localhost;")?;
+    let addresses = config.get_addresses("addr")?.unwrap();
+    assert_eq!(addresses.len(), 1);
+    assert_eq!(
+        addresses.iter().next().unwrap().to_string(),
+        "localhost:9009"
+    );
+    assert_eq!(config.get_addresses("missing")?, None);
+
+    let options = ParseOptions::new().duplicate_keys(DuplicateKeys::Collect);
+    let config = parse_conf_str_with("https::addr=a,b;addr=c:1;", &options)?;
+    let addresses = config.get_addresses("addr")?.unwrap();
+    assert_eq!(
+        addresses.iter().map(|a| a.to_string()).collect::<Vec<_>>(),
+        vec!["a:9000", "b:9000", "c:1"]
+    );
+
+    assert_eq!(default_port("http"), Some(9000));
+    assert_eq!(default_port("tcps"), Some(9009));
+    assert_eq!(default_port("udp"), None);
+    Ok(())
+}
+
+#[test]
+fn address_errors() -> Result<(), ParsingError> {
+    let cases = [
+        ("http::addr=;", 11, "a comma-separated list of addresses"),
+        (
+            "http::addr=a,,b;",
+            13,
+            "a comma-separated list of addresses",
+        ),
+        (
+            "http::addr=a:9000,b:;",
+            20,
+            "a port number between 1 and 65535",
+        ),
+        ("http::addr=a:0;", 13, "a port number between 1 and 65535"),
+        (
+            "http::addr=a:65536;",
+            13,
+            "a port number between 1 and 65535",
+        ),
+        ("http::addr=a:+1;", 13, "a port number between 1 and 65535"),
+        (
+            "http::addr=[::1]9000;",
+            16,
+            "a port number between 1 and 65535",
+        ),
+        ("udp::addr=a;", 11, "a port number between 1 and 65535"),
+        (
+            "http::addr=x,::1:9000;",
+            13,
+            "an IPv6 address enclosed in brackets, e.g. `[::1]:9000`",
+        ),
+        (
+            "http::addr=[::1;",
+            11,
+            "an IPv6 address enclosed in brackets, e.g. `[::1]:9000`",
+        ),
+        (
+            "http::addr=[nope]:1;",
+            11,
+            "an IPv6 address enclosed in brackets, e.g. `[::1]:9000`",
+        ),
+        ("http::addr=a b:1;", 11, "a host name or IP address"),
+        ("http::addr=-a:1;", 11, "a host name or IP address"),
+        ("http::addr=a;;b;", 11, "a host name or IP address"),
+        ("http::addr=999.1.1.1;", 11, "a host name or IP address"),
+        (
+            "http::addr=1.2.3.4.5:9000;",
+            11,
+            "a host name or IP address",
+        ),
+        (
+            "http::x=1;;2;addr=a:1,b:x;",
+            24,
+            "a port number between 1 and 65535",
+        ),
+    ];
+    for (input, position, expected) in cases {
+        let config = parse_conf_str(input)?;
+        let err = config.get_addresses("addr").unwrap_err();
+        assert_eq!(err.position(), Some(position), "{}", input);
+        assert_eq!(err.expected(), expected, "{}", input);
+    }
+    Ok(())
+}
+
+#[test]
+fn to_url() -> Result<(), Box<dyn std::error::Error>> {
+    let config = parse_conf_str("https::addr=h:9000;username=u;password=p;")?;
+    assert_eq!(config.to_url()?, "https://u:p@h:9000/");
+
+    let config = parse_conf_str(
+        "http::username=us er;addr=[::1]:9000;password=p@ss:w/rd;;%;\
+         retry_timeout=500;path=/a b+c?d&e=f#é;",
+    )?;
+    let url = config.to_url()?;
+    assert_eq!(
+        url,
+        "http://us%20er:p%40ss%3Aw%2Frd%3B%25@[::1]:9000/\
+         ?retry_timeout=500&path=%2Fa%20b%2Bc%3Fd%26e%3Df%23%C3%A9"
+    );
+    let round_trip = ConfStr::from_url(&url)?;
+    assert_eq!(
+        round_trip.to_conf_string()?,
+        "http::addr=[::1]:9000;username=us er;password=p@ss:w/rd;;%;\
+         retry_timeout=500;path=/a b+c?d&e=f#é;"
+    );
+
+    let config = parse_conf_str("tcp::addr=localhost;password=p;")?;
+    assert_eq!(config.to_url()?, "tcp://:p@localhost/");
+
+    let cases = [
+        ("_tcp::addr=localhost;", UrlErrorKind::InvalidScheme),
+        ("http::username=u;", UrlErrorKind::InvalidAddress),
+        ("http::addr=a,b;", UrlErrorKind::InvalidAddress),
+        (
+            "https::addr=h:9000;username=;",
+            UrlErrorKind::InvalidValue("username".to_string()),
+        ),
+        (
+            "https::addr=h:9000;username=;password=p;",
+            UrlErrorKind::InvalidValue("username".to_string()),
+        ),
+    ];
+    for (input, kind) in cases {
+        let err = parse_conf_str(input)?.to_url().unwrap_err();
+        assert_eq!(err.kind(), &kind, "{}", input);
+        assert_eq!(err.position(), None);
+    }
+
+    let options = ParseOptions::new().duplicate_keys(DuplicateKeys::Collect);
+    let config = parse_conf_str_with("http::addr=a;x=1;x=2;", &options)?;
+    assert_eq!(
+        config.to_url().unwrap_err().to_string(),
+        "duplicate key \"x\""
+    );
+
+    let config = ConfStr::new(
+        "http".to_string(),
+        [
+            ("addr".to_string(), "a".to_string()),
+            ("password".to_string(), "se\u{7}cret".to_string()),
+        ]
+        .into_iter()
+        .collect(),
+    );
+    assert_eq!(
+        config.to_url().unwrap_err().to_string(),
+        "invalid value for key \"password\""
+    );
+    Ok(())
+}
+
+#[test]
+fn from_url() -> Result<(), Box<dyn std::error::Error>> {
+    let config = ConfStr::from_url("https://u:p@h:9000/?auto_flush=off&x=a+b%2B&&empty=&flag")?;
+    assert_eq!(
+        config.to_conf_string()?,
+        "https::addr=h:9000;username=u;password=p;auto_flush=off;x=a b+;empty=;flag=;"
+    );
+    let config = ConfStr::from_url("tcp://localhost")?;
+    assert_eq!(config.to_conf_string()?, "tcp::addr=localhost;");
+    let config = ConfStr::from_url("HTTPS://db:9000")?;
+    assert_eq!(config.to_conf_string()?, "https::addr=db:9000;");
+    let config = ConfStr::from_url("http://user@10.0.0.1:9000?x=1")?;
+    assert_eq!(
+        config.to_conf_string()?,
+        "http::addr=10.0.0.1:9000;username=user;x=1;"
+    );
+
+    let cases = [
+        ("localhost:9000", UrlErrorKind::InvalidScheme, 0),
+        ("1http://localhost", UrlErrorKind::InvalidScheme, 0),
+        ("http://secret@/", UrlErrorKind::InvalidAddress, 14),
+        ("http://u:p@h:secret/", UrlErrorKind::InvalidAddress, 11),
+        ("http://h/path", UrlErrorKind::UnsupportedPath, 8),
+        ("http://h/#secret", UrlErrorKind::UnsupportedFragment, 9),
+        (
+            "http://u:se%2cret%zz@h",
+            UrlErrorKind::InvalidPercentEncoding,
+            17,
+        ),
+        ("http://u:%ff@h", UrlErrorKind::InvalidPercentEncoding, 9),
+        ("http://h/?a=1&bad-key=x", UrlErrorKind::InvalidKey, 14),
+        ("http://h/?se.cret", UrlErrorKind::InvalidKey, 10),
+        (
+            "http://h/?password=a%09b",
+            UrlErrorKind::InvalidValue("password".to_string()),
+            10,
+        ),
+        (
+            "http://u@h/?username=v",
+            UrlErrorKind::DuplicateKey("username".to_string()),
+            12,
+        ),
+        (
+            "http://h/?addr=h",
+            UrlErrorKind::DuplicateKey("addr".to_string()),
+            10,
+        ),
+    ];
+    for (url, kind, position) in cases {
+        let err = ConfStr::from_url(url).unwrap_err();
+        assert_eq!(err.kind(), &kind, "{}", url);
+        assert_eq!(err.position(), Some(position), "{}", url);
+        assert!(!err.to_string().contains("secret"), "{}", url);
+    }
+    assert_eq!(
+        ConfStr::from_url("http://u:%ff@h").unwrap_err().to_string(),
+        "invalid percent-encoding at position 9"
+    );
+    Ok(())
+}
+
+fn arb_conf_str() -> impl Strategy<Value = ConfStr> {
+    let ident = "[a-zA-Z0-9_]{1,12}";
+    let value = "[^\\x00-\\x1f\\x7f-\\u{9f}]{0,16}";
+    (ident, prop::collection::vec((ident, value), 0..8))
+        .prop_map(|(service, params)| ConfStr::new(service, params.into_iter().collect()))
+}
+
+proptest! {
+    #[test]
+    fn serialize_round_trip(config in arb_conf_str()) {
+        let serialized = config.to_conf_string().unwrap();
+        let parsed = parse_conf_str(&serialized).unwrap();
+        prop_assert_eq!(parsed, config);
+    }
+}